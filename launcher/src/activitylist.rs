@@ -0,0 +1,47 @@
+use thyme_core::activitylist::{parse_activity_list, ActivityListEntry};
+
+use ct_lib_core::path_exists;
+
+pub const ACTIVITY_LIST_FILEPATH: &str = "activity_names.txt";
+
+pub fn reload_activity_list() -> Vec<ActivityListEntry> {
+    load_activity_list().unwrap_or_else(|error| panic!("{}", error))
+}
+
+/// Reads the activity list and creates it with an example text if it does not exist yet
+pub fn load_activity_list() -> Result<Vec<ActivityListEntry>, String> {
+    // Auto-rename old project names file name
+    if path_exists("project_names.txt") {
+        std::fs::rename("project_names.txt", ACTIVITY_LIST_FILEPATH).map_err(|error| {
+            format!(
+                "Could not rename old 'project_names.txt' to 'activity_names.txt' - {}",
+                error
+            )
+        })?;
+    }
+
+    if !path_exists(ACTIVITY_LIST_FILEPATH) {
+        let exampletext = format!(
+            "Welcome to Thyme! :)
+You can add your own activity names here
+by modifying '{}'!
+Each activity name will be its own line in '{}'.
+Up to 9 are shown at once, scroll for more.
+Why not try out modifying '{}' now? 
+(You don't need to close Thyme for this)
+I will be waiting here",
+            ACTIVITY_LIST_FILEPATH, ACTIVITY_LIST_FILEPATH, ACTIVITY_LIST_FILEPATH
+        );
+        std::fs::write(ACTIVITY_LIST_FILEPATH, &exampletext).map_err(|error| {
+            format!(
+                "Could not write to '{}' - {}",
+                &ACTIVITY_LIST_FILEPATH, error
+            )
+        })?;
+    }
+
+    let content = std::fs::read_to_string(ACTIVITY_LIST_FILEPATH)
+        .map_err(|error| format!("Could not read '{}' - {}", &ACTIVITY_LIST_FILEPATH, error))?;
+    parse_activity_list(&content)
+        .map_err(|error| format!("Invalid '{}' - {}", &ACTIVITY_LIST_FILEPATH, error))
+}
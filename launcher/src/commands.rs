@@ -0,0 +1,682 @@
+use crate::activitylist::{reload_activity_list, ACTIVITY_LIST_FILEPATH};
+use crate::config::{open_sqlite_storage, Config, CONFIG_FILEPATH, SQLITE_DATABASE_FILEPATH};
+
+use thyme_core::billing::{Invoice, InvoiceFormat};
+use thyme_core::check::check_database;
+use thyme_core::gitlog::GitActivityLog;
+use thyme_core::query::{Query, QUERY_TERMS_HELP};
+use thyme_core::report_html::generate_day_report_html;
+use thyme_core::report_markdown::generate_standup_markdown;
+use thyme_core::rounding::{RoundingMode, RoundingPolicy, RoundingScope};
+use thyme_core::statistics::Statistics;
+use thyme_core::storage::copy_timesheets;
+use thyme_core::time::TimeDuration;
+use thyme_core::{DayEntry, RealClock, ReportFormat, SharedClock, SharedStorage, TextFileStorage};
+
+use chrono::prelude::*;
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "Usage:
+  thyme
+      Starts the interactive timetracker
+  thyme --profile NAME [<command> ...]
+      Uses the profile with the given name. Every profile keeps its own timesheets, activity
+      list and '{config}' in the subdirectory 'profiles/NAME'. The profile can
+      also be chosen via the THYME_PROFILE environment variable.
+  thyme invoice <from> <to> [--client NAME] [--format text|markdown|html]
+                            [--round-up MINUTES] [--output FILE]
+      Creates an invoice for all activities with an hourly rate between the given dates
+      (inclusive). Dates are given as YYYY-MM-DD, 'today' or 'yesterday'. Durations are
+      rounded according to the rounding rules in '{config}' unless '--round-up' is given.
+  thyme report [<date>] [--format text|markdown|html|standup] [--output FILE]
+      Prints the report of the given day (default: today). The default format is taken
+      from '{config}'.
+  thyme standup [<date>] [--output FILE]
+      Prints a standup summary listing the activities of the given day (default: today) and
+      of the previous tracked day followed by a notes section to fill in by hand.
+  thyme reclassify [<date>|week]
+      Lists the numbered intervals of non-specific work of the given day (default: today) or
+      of all days of the current week.
+  thyme reclassify <date> <number> <activity>[=HH:MM] [<activity>[=HH:MM] ...]
+      Assigns the non-specific interval with the given number to the given activities in
+      order. Every activity but the last one needs a duration, the last one gets the rest of
+      the interval unless a duration is given for it too.
+  thyme query <term> [<term> ...] [--format text|markdown] [--output FILE]
+      Sums up the durations of all activities matching all of the given terms, i.e.
+      'thyme query date:2021-03 name:Bugfixing -weekday:fri by:week'. Known terms:
+{query}
+  thyme statistics [<from> <to>] [--format text|markdown] [--output FILE]
+      Prints statistics about the days between the given dates (default: the last eight
+      weeks): average and median check-in, average end of work and break, days over the
+      preferred work time, the longest activity, a sparkline of the daily work totals and
+      the weekly share of every activity.
+  thyme rename <old name> [<old name> ...] <new name> [--from <date>] [--to <date>]
+                                                      [--dry-run]
+      Renames the given activities to the new name in all timesheets between the given dates
+      (default: all days), which merges them if several old names are given. The reports of
+      all changed days are regenerated. '--dry-run' only lists the affected days.
+  thyme rebuild-reports
+      Regenerates the daily and weekly reports of all days in the database from their
+      timesheets, i.e. after timesheets were edited by hand.
+  thyme check [--fix]
+      Checks all timesheets for stamps out of order, repeated activities, activities that are
+      not in the activity list, dates that do not match the timesheet, past days that end with
+      an ongoing activity and weekdays without a timesheet. With '--fix' stamps are sorted, repeated activities are merged and activity
+      names that only differ in case from the activity list are corrected.
+  thyme migrate-sqlite
+      Imports all timesheets of the 'database' directory into the SQLite database
+      '{sqlite}'. Existing days in the database are replaced. Afterwards
+      'storage = sqlite' can be set in '{config}'. Needs Thyme to be built with the 'sqlite'
+      feature.";
+
+pub fn usage() -> String {
+    USAGE
+        .replace("{config}", CONFIG_FILEPATH)
+        .replace("{sqlite}", SQLITE_DATABASE_FILEPATH)
+        .replace(
+            "{query}",
+            &QUERY_TERMS_HELP
+                .lines()
+                .map(|line| format!("        {}", line))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+}
+
+/// Runs the command given by `args`. Relative output paths are resolved against
+/// `launch_directory` as the current directory is changed to the data directory beforehand.
+pub fn run_command(args: &[String], launch_directory: &Path) -> Result<(), String> {
+    let command = args[0].as_str();
+    let run: fn(&Arguments) -> Result<(), String> = match command {
+        "invoice" => command_invoice,
+        "report" => |arguments| command_report(arguments, None),
+        "standup" => |arguments| command_report(arguments, Some("standup")),
+        "reclassify" => command_reclassify,
+        "query" => command_query,
+        "statistics" => command_statistics,
+        "check" => command_check,
+        "rename" => command_rename,
+        "rebuild-reports" => command_rebuild_reports,
+        "migrate-sqlite" => command_migrate_sqlite,
+        "help" | "--help" | "-h" => {
+            println!("{}", usage());
+            return Ok(());
+        }
+        _ => return Err(format!("Unknown command '{}'", command)),
+    };
+    // NOTE: The config is only read once we know that the command exists
+    let arguments = Arguments::parse(&args[1..], launch_directory)?;
+    run(&arguments)
+}
+
+fn command_invoice(arguments: &Arguments) -> Result<(), String> {
+    arguments.expect_positional_count(2)?;
+    let date_from = parse_date_argument(&arguments.positional[0], arguments.clock.today())?;
+    let date_to = parse_date_argument(&arguments.positional[1], arguments.clock.today())?;
+    if date_to < date_from {
+        return Err(format!(
+            "End date {} lies before start date {}",
+            date_to, date_from
+        ));
+    }
+    let format = match arguments.option("format") {
+        Some(format) => InvoiceFormat::from_string(format)
+            .ok_or_else(|| format!("Unknown invoice format '{}'", format))?,
+        None => InvoiceFormat::Text,
+    };
+    let rounding = match arguments.option("round-up") {
+        Some(minutes) => RoundingPolicy {
+            granularity_minutes: minutes
+                .parse()
+                .map_err(|error| format!("Invalid rounding minutes '{}': {}", minutes, error))?,
+            mode: RoundingMode::Up,
+            scope: RoundingScope::PerEntry,
+        },
+        None => arguments.config.report.rounding,
+    };
+
+    let activity_list = reload_activity_list();
+    let storage = arguments.open_storage()?;
+    let day_entries =
+        DayEntry::load_range_from_database(date_from, date_to, &arguments.clock, &storage)?;
+    let invoice = Invoice::create(
+        &day_entries,
+        &activity_list,
+        arguments.option("client"),
+        date_from,
+        date_to,
+        &rounding,
+    );
+    if invoice.line_items.is_empty() {
+        eprintln!(
+            "No billable activities found between {} and {} - did you set a 'rate' in '{}'?",
+            date_from, date_to, ACTIVITY_LIST_FILEPATH
+        );
+    }
+
+    write_output(arguments, &invoice.to_string(format))
+}
+
+fn command_report(arguments: &Arguments, format_override: Option<&str>) -> Result<(), String> {
+    let date = match arguments.positional.len() {
+        0 => arguments.clock.today(),
+        1 => parse_date_argument(&arguments.positional[0], arguments.clock.today())?,
+        count => return Err(format!("Expected at most 1 argument but got {}", count)),
+    };
+    let storage = arguments.open_storage()?;
+    let day_entry = DayEntry::load_from_database(date, &arguments.clock, &storage)?
+        .ok_or_else(|| format!("There is no timesheet for {} in the database", date))?;
+    let config = &arguments.config;
+
+    let report = match format_override.or_else(|| arguments.option("format")) {
+        None => day_entry.generate_report_in_format(
+            &config.report,
+            &reload_activity_list(),
+            config.report.format,
+        )?,
+        Some("html") => generate_day_report_html(&day_entry),
+        Some("standup") => {
+            let previous_day =
+                DayEntry::load_previous_from_database(date, &arguments.clock, &storage)?;
+            generate_standup_markdown(&day_entry, previous_day.as_ref())
+        }
+        Some(format) => {
+            let format = ReportFormat::from_string(format)
+                .ok_or_else(|| format!("Unknown report format '{}'", format))?;
+            day_entry.generate_report_in_format(&config.report, &reload_activity_list(), format)?
+        }
+    };
+
+    write_output(arguments, &report)
+}
+
+fn command_reclassify(arguments: &Arguments) -> Result<(), String> {
+    let config = &arguments.config;
+    let storage = arguments.open_storage()?;
+
+    if arguments.positional.len() <= 1 {
+        let (date_from, date_to) = match arguments.positional.first().map(|arg| arg.as_str()) {
+            None => (arguments.clock.today(), arguments.clock.today()),
+            Some("week") => {
+                let today = arguments.clock.today();
+                let week_start =
+                    today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64);
+                (week_start, today)
+            }
+            Some(date) => {
+                let date = parse_date_argument(date, arguments.clock.today())?;
+                (date, date)
+            }
+        };
+
+        for day_entry in
+            DayEntry::load_range_from_database(date_from, date_to, &arguments.clock, &storage)?
+        {
+            let indices = day_entry.get_non_specific_work_indices();
+            if indices.is_empty() {
+                continue;
+            }
+            let git_log = GitActivityLog::collect(
+                &day_entry,
+                &config.report.git_repositories,
+                config.report.git_author.as_deref(),
+            );
+            println!(
+                "Non-specific work on {} ({}):",
+                day_entry.date.format("%A %e. %b"),
+                day_entry.date.format("%Y-%m-%d")
+            );
+            for (number, index) in indices.into_iter().enumerate() {
+                let activity = &day_entry.activities[index];
+                let suggestion = git_log
+                    .suggest_activity_name(index, activity)
+                    .map(|(name, commit_count)| {
+                        format!(" - suggested: [{}] ({} commits)", name, commit_count)
+                    })
+                    .unwrap_or_default();
+                println!(
+                    "  ({}) {}{}",
+                    number + 1,
+                    day_entry.activity_to_string(activity),
+                    suggestion
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    if arguments.positional.len() < 3 {
+        return Err("Expected a date, an interval number and at least one activity".to_owned());
+    }
+    let date = parse_date_argument(&arguments.positional[0], arguments.clock.today())?;
+    let number: usize = arguments.positional[1].parse().map_err(|error| {
+        format!(
+            "Invalid interval number '{}': {}",
+            arguments.positional[1], error
+        )
+    })?;
+    let parts = arguments.positional[2..]
+        .iter()
+        .map(|part| match part.rsplit_once('=') {
+            Some((name, duration)) => TimeDuration::from_string(duration)
+                .map(|duration| (name.to_owned(), Some(duration)))
+                .ok_or_else(|| format!("Invalid duration '{}' (expected HH:MM)", duration)),
+            None => Ok((part.to_owned(), None)),
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let mut day_entry = DayEntry::load_from_database(date, &arguments.clock, &storage)?
+        .ok_or_else(|| format!("There is no timesheet for {} in the database", date))?;
+    let index = number
+        .checked_sub(1)
+        .and_then(|number| {
+            day_entry
+                .get_non_specific_work_indices()
+                .get(number)
+                .copied()
+        })
+        .ok_or_else(|| format!("There is no non-specific interval with number {}", number))?;
+    day_entry.reassign_non_specific_work(index, &parts)?;
+    day_entry.write_back()?;
+    day_entry.write_report(&config.report, &reload_activity_list())?;
+
+    for activity in &day_entry.activities {
+        println!("{}", day_entry.activity_to_string(activity));
+    }
+    Ok(())
+}
+
+fn write_output(arguments: &Arguments, content: &str) -> Result<(), String> {
+    if let Some(filepath) = arguments.option("output") {
+        let filepath = arguments.launch_directory.join(filepath);
+        std::fs::write(&filepath, content)
+            .map_err(|error| format!("Could not write to '{}' - {}", filepath.display(), error))?;
+        println!("Written to '{}'", filepath.display());
+    } else {
+        print!("{}", content);
+    }
+    Ok(())
+}
+
+fn command_query(arguments: &Arguments) -> Result<(), String> {
+    if arguments.positional.is_empty() {
+        return Err("Expected at least one query term".to_owned());
+    }
+    let query = Query::parse(&arguments.positional, arguments.clock.today())?;
+    let storage = arguments.open_storage()?;
+    let day_entries = match (query.date_from, query.date_to) {
+        (Some(date_from), Some(date_to)) => {
+            DayEntry::load_range_from_database(date_from, date_to, &arguments.clock, &storage)?
+        }
+        _ => DayEntry::load_all_from_database(&arguments.clock, &storage)?,
+    };
+    let result = query.run(&day_entries, &reload_activity_list());
+
+    let content = match arguments.option("format").unwrap_or("text") {
+        "text" => result.to_text(),
+        "markdown" => result.to_markdown(),
+        format => {
+            return Err(format!(
+                "Unknown format '{}' - expected 'text' or 'markdown'",
+                format
+            ))
+        }
+    };
+    write_output(arguments, &content)
+}
+
+fn command_statistics(arguments: &Arguments) -> Result<(), String> {
+    let today = arguments.clock.today();
+    let (date_from, date_to) = match arguments.positional.len() {
+        0 => {
+            let week_start =
+                today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64);
+            (week_start - chrono::Duration::weeks(7), today)
+        }
+        2 => (
+            parse_date_argument(&arguments.positional[0], today)?,
+            parse_date_argument(&arguments.positional[1], today)?,
+        ),
+        count => {
+            return Err(format!(
+                "Expected either no dates or a start and an end date but got {} arguments",
+                count
+            ))
+        }
+    };
+    if date_to < date_from {
+        return Err(format!(
+            "End date {} lies before start date {}",
+            date_to, date_from
+        ));
+    }
+
+    let storage = arguments.open_storage()?;
+    let day_entries =
+        DayEntry::load_range_from_database(date_from, date_to, &arguments.clock, &storage)?;
+    let statistics = Statistics::create(
+        date_from,
+        date_to,
+        &day_entries,
+        arguments.config.report.target_work_duration,
+    );
+
+    let content = match arguments.option("format").unwrap_or("text") {
+        "text" => statistics.to_text(),
+        "markdown" => statistics.to_markdown(),
+        format => {
+            return Err(format!(
+                "Unknown format '{}' - expected 'text' or 'markdown'",
+                format
+            ))
+        }
+    };
+    write_output(arguments, &content)
+}
+
+fn command_rename(arguments: &Arguments) -> Result<(), String> {
+    if arguments.positional.len() < 2 {
+        return Err("Expected at least one old and the new activity name".to_owned());
+    }
+    let (new_name, old_names) = arguments.positional.split_last().unwrap();
+    let today = arguments.clock.today();
+    let date_from = arguments
+        .option("from")
+        .map(|date| parse_date_argument(date, today))
+        .transpose()?;
+    let date_to = arguments
+        .option("to")
+        .map(|date| parse_date_argument(date, today))
+        .transpose()?;
+    let dry_run = arguments.flag("dry-run");
+    let storage = arguments.open_storage()?;
+
+    // NOTE: All days are loaded before anything is written so that an invalid timesheet does
+    //       not leave the rename half done
+    let mut day_entries = Vec::new();
+    for date in storage.list_timesheet_dates()? {
+        if date_from.map(|from| date < from).unwrap_or(false)
+            || date_to.map(|to| to < date).unwrap_or(false)
+        {
+            continue;
+        }
+        if let Some(day_entry) = DayEntry::load_from_database(date, &arguments.clock, &storage)? {
+            day_entries.push(day_entry);
+        }
+    }
+
+    let config = &arguments.config;
+    let activity_list = reload_activity_list();
+    let mut changed_days = Vec::new();
+    let mut total = TimeDuration::zero();
+    for mut day_entry in day_entries {
+        let renamed = day_entry.rename_activities(old_names, new_name)?;
+        if renamed.is_empty() {
+            continue;
+        }
+        let day_total = renamed
+            .values()
+            .fold(TimeDuration::zero(), |acc, duration| acc + *duration);
+        println!(
+            "{} ({}): {} ({})",
+            day_entry.date.format("%Y-%m-%d"),
+            day_entry.date.format("%A"),
+            day_total.to_string(),
+            renamed
+                .iter()
+                .map(|(name, duration)| format!("[{}] {}", name, duration.to_string()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        total += day_total;
+        changed_days.push(day_entry);
+    }
+    let day_count = changed_days.len();
+
+    if day_count == 0 {
+        println!("No activities to rename found");
+    } else if dry_run {
+        println!(
+            "Would rename {} on {} days to [{}]",
+            total.to_string(),
+            day_count,
+            new_name
+        );
+    } else {
+        // NOTE: The reports are regenerated after all timesheets are written as the weekly
+        //       reports depend on the other days of the week
+        let mut renamed_dates = Vec::new();
+        for day_entry in changed_days.iter_mut() {
+            day_entry.write_back().map_err(|error| {
+                if renamed_dates.is_empty() {
+                    error
+                } else {
+                    format!(
+                        "{}\nThe following days were already renamed: {}",
+                        error,
+                        renamed_dates.join(", ")
+                    )
+                }
+            })?;
+            renamed_dates.push(day_entry.date.format("%Y-%m-%d").to_string());
+        }
+        println!(
+            "Renamed {} on {} days to [{}]",
+            total.to_string(),
+            day_count,
+            new_name
+        );
+        for day_entry in &changed_days {
+            day_entry.write_report(&config.report, &activity_list)?;
+        }
+    }
+    if let Some(activity) = activity_list
+        .iter()
+        .find(|activity| old_names.contains(&activity.name))
+    {
+        println!(
+            "Note: [{}] is still in '{}'",
+            activity.name, ACTIVITY_LIST_FILEPATH
+        );
+    }
+    Ok(())
+}
+
+fn command_rebuild_reports(arguments: &Arguments) -> Result<(), String> {
+    arguments.expect_positional_count(0)?;
+    let config = &arguments.config;
+    let storage = arguments.open_storage()?;
+    let activity_list = reload_activity_list();
+
+    // NOTE: Broken timesheets are skipped so that they do not keep all other reports stale
+    let mut errors = Vec::new();
+    let mut day_count = 0;
+    let mut last_day_of_weeks: Vec<DayEntry> = Vec::new();
+    for date in storage.list_timesheet_dates()? {
+        let result =
+            DayEntry::load_from_database(date, &arguments.clock, &storage).and_then(|day_entry| {
+                match day_entry {
+                    Some(day_entry) => day_entry
+                        .write_day_reports(&config.report, &activity_list)
+                        .map(|_| Some(day_entry)),
+                    None => Ok(None),
+                }
+            });
+        match result {
+            Ok(Some(day_entry)) => {
+                day_count += 1;
+                let is_same_week = last_day_of_weeks
+                    .last()
+                    .map(|last| last.date.iso_week() == day_entry.date.iso_week())
+                    .unwrap_or(false);
+                if is_same_week {
+                    last_day_of_weeks.pop();
+                }
+                last_day_of_weeks.push(day_entry);
+            }
+            Ok(None) => {}
+            Err(error) => errors.push(error),
+        }
+    }
+    for day_entry in &last_day_of_weeks {
+        if let Err(error) = day_entry.write_week_report(&config.report, &activity_list) {
+            let week = day_entry.date.iso_week();
+            errors.push(format!(
+                "Could not write the report of week {} of {} - {}",
+                week.week(),
+                week.year(),
+                error
+            ));
+        }
+    }
+
+    println!(
+        "Regenerated the reports of {} days and {} weeks",
+        day_count,
+        last_day_of_weeks.len()
+    );
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Some reports could not be regenerated:\n{}",
+            errors.join("\n")
+        ))
+    }
+}
+
+fn command_check(arguments: &Arguments) -> Result<(), String> {
+    arguments.expect_positional_count(0)?;
+    let fix = arguments.flag("fix");
+    let storage = arguments.open_storage()?;
+    let problems = check_database(
+        storage.as_ref(),
+        &reload_activity_list(),
+        arguments.clock.today(),
+        fix,
+    )?;
+    for problem in &problems {
+        println!("{}", problem.to_string());
+    }
+
+    let fixable_count = problems
+        .iter()
+        .filter(|problem| problem.fix.is_some())
+        .count();
+    let fixed_count = problems.iter().filter(|problem| problem.is_fixed).count();
+    if problems.is_empty() {
+        println!("No problems found");
+    } else if fix {
+        println!("Found {} problems, fixed {}", problems.len(), fixed_count);
+    } else if fixable_count > 0 {
+        println!(
+            "Found {} problems, {} can be fixed with 'thyme check --fix'",
+            problems.len(),
+            fixable_count
+        );
+    } else {
+        println!("Found {} problems", problems.len());
+    }
+    Ok(())
+}
+
+fn command_migrate_sqlite(arguments: &Arguments) -> Result<(), String> {
+    arguments.expect_positional_count(0)?;
+    let source = TextFileStorage::new(Path::new(""));
+    let destination = open_sqlite_storage(false)?;
+    let (day_count, errors) = copy_timesheets(&source, destination.as_ref())?;
+    println!(
+        "Imported {} days into '{}'. Set 'storage = sqlite' in '{}' to use it.",
+        day_count, SQLITE_DATABASE_FILEPATH, CONFIG_FILEPATH
+    );
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Some timesheets could not be imported:\n{}",
+            errors.join("\n")
+        ))
+    }
+}
+
+/// Parses a date given as YYYY-MM-DD, 'today' or 'yesterday'
+pub fn parse_date_argument(input: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    match input {
+        "today" => Ok(today),
+        "yesterday" => Ok(today.pred()),
+        _ => NaiveDate::parse_from_str(input, "%Y-%m-%d")
+            .map_err(|error| format!("'{}' is not a valid date (YYYY-MM-DD): {}", input, error)),
+    }
+}
+
+/// Options that do not take a value
+const FLAGS: &[&str] = &["fix", "dry-run"];
+
+struct Arguments {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+    flags: HashSet<String>,
+    launch_directory: PathBuf,
+    clock: SharedClock,
+    config: Config,
+}
+
+impl Arguments {
+    /// Splits the given arguments into positional arguments and options of the form
+    /// `--name value`
+    fn parse(args: &[String], launch_directory: &Path) -> Result<Arguments, String> {
+        let mut result = Arguments {
+            positional: Vec::new(),
+            options: HashMap::new(),
+            flags: HashSet::new(),
+            launch_directory: launch_directory.to_path_buf(),
+            clock: RealClock::shared(),
+            config: Config::load()?,
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if let Some(name) = arg.strip_prefix("--") {
+                if FLAGS.contains(&name) {
+                    result.flags.insert(name.to_owned());
+                    continue;
+                }
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("Missing value for option '{}'", arg))?;
+                result.options.insert(name.to_owned(), value.to_owned());
+            } else {
+                result.positional.push(arg.to_owned());
+            }
+        }
+        Ok(result)
+    }
+
+    /// Opens the storage configured in the config. Only commands that read or write timesheets
+    /// open it, so that i.e. a SQLite storage without SQLite support does not break the others.
+    fn open_storage(&self) -> Result<SharedStorage, String> {
+        self.config.create_storage()
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(|value| value.as_str())
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+
+    fn expect_positional_count(&self, count: usize) -> Result<(), String> {
+        if self.positional.len() != count {
+            Err(format!(
+                "Expected {} arguments but got {}",
+                count,
+                self.positional.len()
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
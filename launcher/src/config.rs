@@ -0,0 +1,279 @@
+use crate::keymap::{Action, Keymap};
+use crate::theme::Theme;
+
+use thyme_core::gitlog::GitRepository;
+use thyme_core::rounding::{RoundingMode, RoundingPolicy, RoundingScope};
+use thyme_core::{ReportFormat, ReportSettings, SharedStorage, TextFileStorage, TimeDuration};
+
+use ct_lib_core::path_exists;
+
+use std::path::Path;
+
+pub const CONFIG_FILEPATH: &str = "thyme_config.txt";
+
+/// Location of the database used by the 'sqlite' storage
+pub const SQLITE_DATABASE_FILEPATH: &str = "database/thyme.sqlite";
+
+const CONFIG_DEFAULT: &str = "# Thyme configuration - lines starting with '#' are ignored
+# You don't need to close Thyme when changing this file
+
+# Rounding of activity durations in reports and invoices (the timesheets always stay exact)
+#   rounding_minutes: Round to multiples of this many minutes (e.g. 6, 15 or 30, 1 = exact)
+#   rounding_mode:    'up', 'down' or 'nearest'
+#   rounding_scope:   'entry' (round every single activity entry)
+#                     'day' (round the total of an activity per day)
+rounding_minutes = 15
+rounding_mode = up
+rounding_scope = entry
+
+# Preferred work time per day (HH:MM) - can be adjusted temporarily in Thyme with '+' and '-'
+preferred_work_time = 08:00
+
+# Reports show a warning if the share of non-specific work exceeds this percentage (0 = never)
+non_specific_warning_percent = 25
+
+# Format of the daily reports: 'text' or 'markdown'
+report_format = text
+
+# Show a timeline of the day below the summary: 'true' or 'false'
+show_timeline = true
+
+# Where timesheets and reports are stored (changes take effect after restarting Thyme):
+#   'text':   human readable text files in the 'database' directory
+#   'sqlite': the SQLite database '{sqlite}' (needs Thyme to be built with the
+#             'sqlite' feature) - use 'thyme migrate-sqlite' once to import the text files
+# With 'sqlite' the text files are still written and can be edited if 'storage_mirror_text_files'
+# is 'true'
+storage = text
+storage_mirror_text_files = true
+
+# Color theme: 'default' (for dark terminals), 'light' (for light terminals) or 'monochrome'
+# NOTE: Setting the environment variable NO_COLOR always disables colors
+theme = default
+
+# Local git repositories whose commits are listed in the daily report under the activity during
+# which they were made. Add one 'git_repository' line per repository. Optionally an activity name
+# can be given that is suggested for non-specific work during which commits were made.
+# By default only commits by the 'user.email' configured in each repository are listed, this can
+# be changed with 'git_author'.
+# git_repository = ~/projects/thyme | activity: Thyme development
+# git_author = me@example.com
+
+# Key bindings in the form 'key_<action> = <key>, <key>, ...'
+# A key is a single character or one of 'esc', 'enter', 'space', 'tab', 'backspace', 'delete',
+# 'insert', 'home', 'end', 'pageup', 'pagedown', 'up', 'down', 'left', 'right', 'f1' - 'f12'
+# optionally prefixed with modifiers like 'ctrl+', 'alt+' or 'shift+' (i.e. 'ctrl+c')
+# Press '?' in Thyme to see all current key bindings
+key_toggle_work = x
+key_activity_1 = 1
+key_activity_2 = 2
+key_activity_3 = 3
+key_activity_4 = 4
+key_activity_5 = 5
+key_activity_6 = 6
+key_activity_7 = 7
+key_activity_8 = 8
+key_activity_9 = 9
+key_increase_work_time = +
+key_decrease_work_time = -
+key_select_previous = up, k
+key_select_next = down, j
+key_reclassify = r
+key_split_interval = s
+key_previous_day = left, h
+key_next_day = right, l
+key_calendar = c
+key_edit_day = e
+key_help = ?
+key_quit = esc, ctrl+c
+";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StorageBackend {
+    Text,
+    Sqlite,
+}
+
+impl StorageBackend {
+    pub fn from_string(input: &str) -> Option<StorageBackend> {
+        match input {
+            "text" => Some(StorageBackend::Text),
+            "sqlite" => Some(StorageBackend::Sqlite),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub preferred_working_time: TimeDuration,
+    pub report: ReportSettings,
+    pub show_timeline: bool,
+    pub storage: StorageBackend,
+    pub storage_mirror_text_files: bool,
+    pub theme: Theme,
+    pub keymap: Keymap,
+}
+
+impl Config {
+    pub fn default() -> Config {
+        Config {
+            preferred_working_time: TimeDuration { minutes: 8 * 60 },
+            // NOTE: Invoices have always been rounded up to 15 minutes per entry so we keep that
+            //       for config files written before the rounding settings existed
+            report: ReportSettings {
+                rounding: RoundingPolicy {
+                    granularity_minutes: 15,
+                    mode: RoundingMode::Up,
+                    scope: RoundingScope::PerEntry,
+                },
+                ..ReportSettings::default()
+            },
+            show_timeline: true,
+            storage: StorageBackend::Text,
+            storage_mirror_text_files: true,
+            theme: Theme::default(),
+            keymap: Keymap::default(),
+        }
+    }
+
+    pub fn reload() -> Config {
+        Config::load().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Reads the config file and creates it with the default settings if it does not exist yet
+    pub fn load() -> Result<Config, String> {
+        if !path_exists(CONFIG_FILEPATH) {
+            let content = CONFIG_DEFAULT.replace("{sqlite}", SQLITE_DATABASE_FILEPATH);
+            std::fs::write(CONFIG_FILEPATH, content)
+                .map_err(|error| format!("Could not write to '{}' - {}", CONFIG_FILEPATH, error))?;
+        }
+
+        let content = std::fs::read_to_string(CONFIG_FILEPATH)
+            .map_err(|error| format!("Could not read '{}' - {}", CONFIG_FILEPATH, error))?;
+        let mut result = Config::from_string(&content)?;
+        if Theme::is_color_disabled_by_environment() {
+            result.theme = Theme::monochrome();
+        }
+        Ok(result)
+    }
+
+    fn from_string(input: &str) -> Result<Config, String> {
+        let mut result = Config::default();
+        for line in input
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .filter(|line| !line.starts_with('#'))
+        {
+            let (key, value) = line.split_once('=').ok_or_else(|| {
+                format!(
+                    "Line '{}' in '{}' is not of the form 'key = value'",
+                    line, CONFIG_FILEPATH
+                )
+            })?;
+            let key = key.trim();
+            let value = value.trim();
+
+            let is_valid = match key {
+                "preferred_work_time" => TimeDuration::from_string(value).map(|duration| {
+                    result.preferred_working_time = duration;
+                    result.report.target_work_duration = duration;
+                }),
+                "non_specific_warning_percent" => value
+                    .parse()
+                    .ok()
+                    .filter(|percent| *percent <= 100)
+                    .map(|percent| result.report.non_specific_warning_percent = percent),
+                "rounding_minutes" => value
+                    .parse()
+                    .ok()
+                    .filter(|minutes| *minutes > 0)
+                    .map(|minutes| result.report.rounding.granularity_minutes = minutes),
+                "rounding_mode" => {
+                    RoundingMode::from_string(value).map(|mode| result.report.rounding.mode = mode)
+                }
+                "rounding_scope" => RoundingScope::from_string(value)
+                    .map(|scope| result.report.rounding.scope = scope),
+                "report_format" => {
+                    ReportFormat::from_string(value).map(|format| result.report.format = format)
+                }
+                "show_timeline" => value
+                    .parse()
+                    .ok()
+                    .map(|show_timeline| result.show_timeline = show_timeline),
+                "storage" => {
+                    StorageBackend::from_string(value).map(|storage| result.storage = storage)
+                }
+                "storage_mirror_text_files" => value
+                    .parse()
+                    .ok()
+                    .map(|mirror| result.storage_mirror_text_files = mirror),
+                "theme" => Theme::from_name(value).map(|theme| result.theme = theme),
+                "git_repository" => GitRepository::from_string(value)
+                    .map(|repository| result.report.git_repositories.push(repository)),
+                "git_author" => {
+                    result.report.git_author =
+                        Some(value.to_owned()).filter(|author| !author.is_empty());
+                    Some(())
+                }
+                _ if key.starts_with("key_") => {
+                    let action = Action::from_name(&key["key_".len()..]).ok_or_else(|| {
+                        format!("Unknown key binding '{}' in '{}'", key, CONFIG_FILEPATH)
+                    })?;
+                    result.keymap.rebind(action, value)
+                }
+                _ => {
+                    return Err(format!(
+                        "Unknown setting '{}' in '{}'",
+                        key, CONFIG_FILEPATH
+                    ))
+                }
+            }
+            .is_some();
+            if !is_valid {
+                return Err(format!(
+                    "Invalid value '{}' for '{}' in '{}'",
+                    value, key, CONFIG_FILEPATH
+                ));
+            }
+        }
+        if let Some((key, action, other_action)) = result.keymap.find_conflict() {
+            return Err(format!(
+                "The key '{}' is bound to both 'key_{}' and 'key_{}' in '{}'",
+                key.to_string(),
+                action.name(),
+                other_action.name(),
+                CONFIG_FILEPATH
+            ));
+        }
+        Ok(result)
+    }
+
+    /// Opens the configured storage in the current directory
+    pub fn create_storage(&self) -> Result<SharedStorage, String> {
+        match self.storage {
+            StorageBackend::Text => Ok(TextFileStorage::shared(Path::new(""))),
+            StorageBackend::Sqlite => open_sqlite_storage(self.storage_mirror_text_files),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub fn open_sqlite_storage(mirror_text_files: bool) -> Result<SharedStorage, String> {
+    use thyme_core::storage_sqlite::SqliteStorage;
+
+    std::fs::create_dir_all("database")
+        .map_err(|error| format!("Could not create path 'database' - {}", error))?;
+    let mirror = if mirror_text_files {
+        Some(TextFileStorage::new(Path::new("")))
+    } else {
+        None
+    };
+    SqliteStorage::shared(SQLITE_DATABASE_FILEPATH, mirror)
+}
+
+#[cfg(not(feature = "sqlite"))]
+pub fn open_sqlite_storage(_mirror_text_files: bool) -> Result<SharedStorage, String> {
+    Err("Thyme was built without SQLite support - build it with '--features sqlite'".to_owned())
+}
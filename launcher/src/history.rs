@@ -0,0 +1,594 @@
+use crate::config::Config;
+use crate::keymap::Action;
+use crate::timeline::create_timeline_screen;
+use crate::ACTIVITY_LIST_VISIBLE_COUNT;
+
+use thyme_core::dayentry::write_durations_summary;
+use thyme_core::{ActivityListEntry, DayEntry, TimeDuration};
+
+use chrono::prelude::*;
+
+use std::fmt::Write;
+
+/// Screen for browsing the days before today, either one day at a time or as a calendar of a
+/// whole month. The shown day is loaded from the database and can only be changed after
+/// confirming it. Today's entry stays owned by the main loop.
+pub struct HistoryScreen {
+    today: NaiveDate,
+    date: NaiveDate,
+    /// `None` if nothing was tracked on the shown day or if its timesheet is broken
+    day_entry: Option<DayEntry>,
+    load_error: Option<String>,
+    is_calendar_shown: bool,
+    /// True after editing was requested once and waits for the confirmation
+    is_edit_requested: bool,
+    is_editable: bool,
+    /// Index into the work intervals of the shown day while editing
+    selected: usize,
+    /// Why the last assignment failed
+    error: Option<String>,
+    /// Work totals of the tracked days in the month of the shown day
+    month_totals: Vec<(NaiveDate, TimeDuration)>,
+    template: DayEntry,
+}
+
+fn month_start(date: NaiveDate) -> NaiveDate {
+    NaiveDate::from_ymd(date.year(), date.month(), 1)
+}
+
+fn month_end(date: NaiveDate) -> NaiveDate {
+    let next_month_start = if date.month() == 12 {
+        NaiveDate::from_ymd(date.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd(date.year(), date.month() + 1, 1)
+    };
+    next_month_start.pred()
+}
+
+impl HistoryScreen {
+    /// Opens the screen at yesterday or with the calendar of the current month
+    pub fn new(today: &DayEntry, is_calendar_shown: bool) -> HistoryScreen {
+        let mut result = HistoryScreen {
+            today: today.date,
+            date: today.date,
+            day_entry: None,
+            load_error: None,
+            is_calendar_shown,
+            is_edit_requested: false,
+            is_editable: false,
+            selected: 0,
+            error: None,
+            month_totals: Vec::new(),
+            template: today.clone(),
+        };
+        let date = if is_calendar_shown {
+            today.date
+        } else {
+            today.date.pred()
+        };
+        result.show_date(date);
+        result.load_month_totals();
+        result
+    }
+
+    fn show_date(&mut self, date: NaiveDate) {
+        let is_other_month = month_start(date) != month_start(self.date);
+        self.date = date;
+        self.is_edit_requested = false;
+        self.is_editable = false;
+        self.selected = 0;
+        self.error = None;
+        // NOTE: Today's entry in memory can be up to a minute ahead of the timesheet that was
+        //       written back last, so we show today as it was when the screen was opened
+        let loaded = if date == self.today {
+            Ok(Some(self.template.clone()))
+        } else {
+            DayEntry::load_from_database(date, &self.template.clock, &self.template.storage)
+        };
+        match loaded {
+            Ok(day_entry) => {
+                self.day_entry = day_entry;
+                self.load_error = None;
+            }
+            Err(error) => {
+                self.day_entry = None;
+                self.load_error = Some(error);
+            }
+        }
+        if is_other_month {
+            self.load_month_totals();
+        }
+    }
+
+    fn load_month_totals(&mut self) {
+        // NOTE: Broken timesheets are left out here, they show their error when selected
+        let mut date = month_start(self.date);
+        self.month_totals.clear();
+        while date <= month_end(self.date) {
+            if date == self.today {
+                self.month_totals
+                    .push((date, self.template.get_work_duration_total()));
+            } else if let Ok(Some(day_entry)) =
+                DayEntry::load_from_database(date, &self.template.clock, &self.template.storage)
+            {
+                self.month_totals
+                    .push((date, day_entry.get_work_duration_total()));
+            }
+            date = date.succ();
+        }
+    }
+
+    /// Returns false if the screen should be closed because the calendar was closed on today
+    pub fn toggle_calendar(&mut self) -> bool {
+        self.is_calendar_shown = !self.is_calendar_shown;
+        self.is_calendar_shown || self.date < self.today
+    }
+
+    /// Moves the shown day by the given number of days. Returns false if the screen should be
+    /// closed because today was reached outside of the calendar.
+    pub fn move_date(&mut self, days: i64) -> bool {
+        let date = self.date + chrono::Duration::days(days);
+        if date >= self.today {
+            if !self.is_calendar_shown {
+                return false;
+            }
+            self.show_date(self.today);
+        } else {
+            self.show_date(date);
+        }
+        true
+    }
+
+    /// Returns the indices of the work intervals of the shown day
+    fn work_indices(&self) -> Vec<usize> {
+        match &self.day_entry {
+            Some(day_entry) => day_entry
+                .activities
+                .iter()
+                .enumerate()
+                .filter(|(_, activity)| activity.is_work)
+                .map(|(index, _)| index)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        let work_indices = self.work_indices();
+        work_indices
+            .get(usize::min(
+                self.selected,
+                work_indices.len().saturating_sub(1),
+            ))
+            .copied()
+    }
+
+    pub fn select_previous(&mut self) {
+        if self.is_calendar_shown {
+            self.move_date(-7);
+        } else {
+            self.selected = self.selected.saturating_sub(1);
+            self.error = None;
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if self.is_calendar_shown {
+            self.move_date(7);
+        } else {
+            self.selected = usize::min(
+                self.selected + 1,
+                self.work_indices().len().saturating_sub(1),
+            );
+            self.error = None;
+        }
+    }
+
+    /// The first request asks for confirmation, the second one makes the shown day editable.
+    /// Today is edited on the main screen and can therefore not be edited here.
+    pub fn request_editing(&mut self) {
+        if self.day_entry.is_none() || self.date >= self.today || self.is_calendar_shown {
+            return;
+        }
+        if self.is_edit_requested {
+            self.is_edit_requested = false;
+            self.is_editable = true;
+        } else if !self.is_editable {
+            self.is_edit_requested = true;
+        }
+    }
+
+    pub fn cancel_edit_request(&mut self) {
+        self.is_edit_requested = false;
+    }
+
+    /// Assigns the selected work interval of the shown day to the given activity and writes
+    /// back the changed timesheet and its reports. The shown day only changes if its timesheet
+    /// could be written.
+    pub fn assign(
+        &mut self,
+        activity_name: &str,
+        config: &Config,
+        activity_list: &[ActivityListEntry],
+    ) {
+        if !self.is_editable || self.is_calendar_shown {
+            return;
+        }
+        let index = match self.selected_index() {
+            Some(index) => index,
+            None => return,
+        };
+        if let Some(day_entry) = self.day_entry.as_mut() {
+            let mut changed = day_entry.clone();
+            match changed
+                .reassign_activity(index, activity_name)
+                .and_then(|_| changed.write_back())
+            {
+                Ok(()) => {
+                    self.error = changed.write_report(&config.report, activity_list).err();
+                    *day_entry = changed;
+                }
+                Err(error) => self.error = Some(error),
+            }
+        }
+        // NOTE: Reassigning may merge the selected interval with its neighbours
+        self.selected = usize::min(self.selected, self.work_indices().len().saturating_sub(1));
+        self.load_month_totals();
+    }
+
+    pub fn render(
+        &self,
+        activity_list: &[ActivityListEntry],
+        activity_list_scroll_offset: usize,
+        config: &Config,
+        terminal_width: usize,
+    ) -> String {
+        if self.is_calendar_shown {
+            self.render_calendar(config)
+        } else {
+            self.render_day(
+                activity_list,
+                activity_list_scroll_offset,
+                config,
+                terminal_width,
+            )
+        }
+    }
+
+    fn render_day(
+        &self,
+        activity_list: &[ActivityListEntry],
+        activity_list_scroll_offset: usize,
+        config: &Config,
+        terminal_width: usize,
+    ) -> String {
+        let theme = &config.theme;
+        let keymap = &config.keymap;
+        let mut result = String::new();
+
+        writeln!(
+            result,
+            "{} -- {}",
+            theme.paint(
+                theme.heading,
+                &format!("History of {}", self.date.format("%A %e. %b (%d.%m.%Y)"))
+            ),
+            if self.is_editable {
+                theme.paint(theme.overtime, "editing")
+            } else {
+                theme.paint(theme.dimmed, "read-only")
+            }
+        )
+        .unwrap();
+        if self.is_edit_requested {
+            writeln!(
+                result,
+                "{}",
+                theme.paint(
+                    theme.overtime,
+                    &format!(
+                        "Press ({}) again to edit this day - changes are written to the database",
+                        keymap.key_hint(Action::EditDay)
+                    )
+                )
+            )
+            .unwrap();
+        } else {
+            writeln!(result).unwrap();
+        }
+        writeln!(
+            result,
+            "\n{}\n",
+            theme.paint(
+                theme.separator,
+                "================================================="
+            )
+        )
+        .unwrap();
+
+        match (&self.day_entry, &self.load_error) {
+            (_, Some(error)) => {
+                writeln!(result, "{}\n", theme.paint(theme.overtime, error)).unwrap();
+            }
+            (None, None) => {
+                writeln!(result, "Nothing was tracked on this day.\n").unwrap();
+            }
+            (Some(day_entry), None) => {
+                match (
+                    day_entry.first_checkin_time(),
+                    day_entry.last_checkout_time(),
+                ) {
+                    (Some(checkin), Some(checkout)) => writeln!(
+                        result,
+                        "You started at {} and left at {}\n",
+                        checkin.to_string(),
+                        checkout.to_string()
+                    )
+                    .unwrap(),
+                    (Some(checkin), None) => writeln!(
+                        result,
+                        "You started at {} and did not check out\n",
+                        checkin.to_string()
+                    )
+                    .unwrap(),
+                    _ => writeln!(result, "\n").unwrap(),
+                }
+
+                writeln!(result, "{}", write_durations_summary(day_entry).trim_end()).unwrap();
+                writeln!(
+                    result,
+                    "\n{}\n",
+                    theme.paint(
+                        theme.separator,
+                        "================================================="
+                    )
+                )
+                .unwrap();
+
+                if config.show_timeline {
+                    writeln!(
+                        result,
+                        "{}",
+                        create_timeline_screen(day_entry, None, theme, terminal_width)
+                    )
+                    .unwrap();
+                }
+
+                let selected_index = self.selected_index();
+                for (index, activity) in day_entry.activities.iter().enumerate() {
+                    if !activity.is_work && activity.time_end.is_none() {
+                        continue;
+                    }
+                    let line = day_entry.activity_to_string(activity);
+                    if self.is_editable && Some(index) == selected_index {
+                        writeln!(
+                            result,
+                            "{}",
+                            theme.paint(theme.active, &format!("> {}", line))
+                        )
+                        .unwrap();
+                    } else if activity.is_work {
+                        writeln!(result, "  {}", line).unwrap();
+                    } else {
+                        writeln!(result, "  {}", theme.paint(theme.dimmed, &line)).unwrap();
+                    }
+                }
+                if let Some(error) = &self.error {
+                    writeln!(
+                        result,
+                        "\n{}",
+                        theme.paint(theme.overtime, &format!("Could not assign: {}", error))
+                    )
+                    .unwrap();
+                }
+                writeln!(result).unwrap();
+
+                if self.is_editable {
+                    for (index, activity) in activity_list
+                        .iter()
+                        .enumerate()
+                        .skip(activity_list_scroll_offset)
+                        .take(ACTIVITY_LIST_VISIBLE_COUNT)
+                    {
+                        let key = keymap
+                            .key_hint(Action::ActivitySlot(index - activity_list_scroll_offset));
+                        writeln!(
+                            result,
+                            "{} Assign selected interval to [{}]",
+                            theme.paint(theme.key_hint, &format!("({})", key)),
+                            theme.paint(theme.activity_style(&activity.name), &activity.name)
+                        )
+                        .unwrap();
+                    }
+                    writeln!(result).unwrap();
+                }
+            }
+        }
+
+        let mut hints = vec![
+            (
+                format!(
+                    "({}/{})",
+                    keymap.key_hint(Action::PreviousDay),
+                    keymap.key_hint(Action::NextDay)
+                ),
+                "Previous/next day",
+            ),
+            (
+                format!("({})", keymap.key_hint(Action::ToggleCalendar)),
+                "Calendar",
+            ),
+        ];
+        if self.is_editable {
+            hints.push((
+                format!(
+                    "({}/{})",
+                    keymap.key_hint(Action::SelectPrevious),
+                    keymap.key_hint(Action::SelectNext)
+                ),
+                "Select interval",
+            ));
+        } else if self.day_entry.is_some() {
+            hints.push((
+                format!("({})", keymap.key_hint(Action::EditDay)),
+                "Edit this day",
+            ));
+        }
+        hints.push((
+            format!("({})", keymap.key_hint(Action::Quit)),
+            "Back to today",
+        ));
+        let hints: Vec<String> = hints
+            .into_iter()
+            .map(|(keys, description)| {
+                format!("{} {}", theme.paint(theme.key_hint, &keys), description)
+            })
+            .collect();
+        writeln!(result, "{}", hints.join("  ")).unwrap();
+
+        result
+    }
+
+    fn render_calendar(&self, config: &Config) -> String {
+        let theme = &config.theme;
+        let keymap = &config.keymap;
+        let target = config.preferred_working_time;
+        let mut result = String::new();
+
+        writeln!(
+            result,
+            "{}",
+            theme.paint(
+                theme.heading,
+                &format!("Calendar of {}", self.date.format("%B %Y"))
+            )
+        )
+        .unwrap();
+        writeln!(
+            result,
+            "{} reached, {} missed the preferred work time of {}",
+            theme.paint(theme.active, "Green"),
+            theme.paint(theme.overtime, "red"),
+            target.to_string()
+        )
+        .unwrap();
+        writeln!(
+            result,
+            "\n{}\n",
+            theme.paint(
+                theme.separator,
+                "================================================="
+            )
+        )
+        .unwrap();
+
+        // NOTE: Every cell is 10 characters wide plus one space of separation
+        for weekday in &["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"] {
+            write!(result, " {:<10}", weekday).unwrap();
+        }
+        writeln!(result).unwrap();
+
+        let first = month_start(self.date);
+        write!(
+            result,
+            "{}",
+            " ".repeat(11 * first.weekday().num_days_from_monday() as usize)
+        )
+        .unwrap();
+        let mut date = first;
+        while date <= month_end(self.date) {
+            let total = self
+                .month_totals
+                .iter()
+                .find(|(total_date, _)| *total_date == date)
+                .map(|(_, total)| *total);
+            let marker = if date == self.date { '>' } else { ' ' };
+            let cell = match total {
+                Some(total) => format!("{}{:>2} {:>6}", marker, date.day(), total.to_string()),
+                None => format!("{}{:>2}       ", marker, date.day()),
+            };
+            let style = match total {
+                _ if date > self.today => theme.dimmed,
+                Some(total) if total.minutes >= target.minutes => theme.active,
+                Some(_) => theme.overtime,
+                None => theme.dimmed,
+            };
+            let cell = if date == self.date {
+                theme.paint(theme.heading, &theme.paint(style, &cell))
+            } else {
+                theme.paint(style, &cell)
+            };
+            write!(result, "{} ", cell).unwrap();
+            if date.weekday() == Weekday::Sun {
+                writeln!(result).unwrap();
+            }
+            date = date.succ();
+        }
+        writeln!(result, "\n").unwrap();
+
+        let month_total = self
+            .month_totals
+            .iter()
+            .fold(TimeDuration::zero(), |acc, (_, total)| acc + *total);
+        let reached_count = self
+            .month_totals
+            .iter()
+            .filter(|(_, total)| total.minutes >= target.minutes)
+            .count();
+        writeln!(
+            result,
+            "Month total: {} on {} days, {} of them reached the preferred work time",
+            month_total.to_string(),
+            self.month_totals.len(),
+            reached_count
+        )
+        .unwrap();
+        match (&self.day_entry, &self.load_error) {
+            (_, Some(error)) => writeln!(result, "{}", theme.paint(theme.overtime, error)).unwrap(),
+            (Some(day_entry), None) => writeln!(
+                result,
+                "{}: {} work, {} break",
+                self.date.format("%A %d.%m."),
+                day_entry.get_work_duration_total().to_string(),
+                day_entry.get_break_duration().to_string()
+            )
+            .unwrap(),
+            (None, None) => {
+                writeln!(result, "{}: nothing tracked", self.date.format("%A %d.%m.")).unwrap()
+            }
+        }
+        writeln!(result).unwrap();
+
+        writeln!(
+            result,
+            "{} Previous/next day  {} Previous/next week  {} Show day  {} Back to today",
+            theme.paint(
+                theme.key_hint,
+                &format!(
+                    "({}/{})",
+                    keymap.key_hint(Action::PreviousDay),
+                    keymap.key_hint(Action::NextDay)
+                )
+            ),
+            theme.paint(
+                theme.key_hint,
+                &format!(
+                    "({}/{})",
+                    keymap.key_hint(Action::SelectPrevious),
+                    keymap.key_hint(Action::SelectNext)
+                )
+            ),
+            theme.paint(
+                theme.key_hint,
+                &format!("({})", keymap.key_hint(Action::ToggleCalendar))
+            ),
+            theme.paint(
+                theme.key_hint,
+                &format!("({})", keymap.key_hint(Action::Quit))
+            ),
+        )
+        .unwrap();
+
+        result
+    }
+}
@@ -0,0 +1,317 @@
+use crate::ACTIVITY_LIST_VISIBLE_COUNT;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Begins work if we are on a break and takes a break otherwise
+    ToggleWork,
+    /// Starts or stops the activity in the given row of the currently shown activity list
+    ActivitySlot(usize),
+    IncreaseWorkTime,
+    DecreaseWorkTime,
+    /// Moves the selection up (or scrolls the activity list if there is no selection)
+    SelectPrevious,
+    /// Moves the selection down (or scrolls the activity list if there is no selection)
+    SelectNext,
+    /// Opens/closes the screen for assigning non-specific work to activities
+    ToggleReclassify,
+    /// Splits the selected non-specific work interval in two halves
+    SplitInterval,
+    /// Shows the day before the currently shown day
+    PreviousDay,
+    /// Shows the day after the currently shown day
+    NextDay,
+    /// Opens/closes the calendar of the month of the currently shown day
+    ToggleCalendar,
+    /// Allows changing a day in the past after pressing it a second time to confirm
+    EditDay,
+    ToggleHelp,
+    Quit,
+}
+
+impl Action {
+    pub fn all() -> Vec<Action> {
+        let mut result = vec![Action::ToggleWork];
+        result.extend((0..ACTIVITY_LIST_VISIBLE_COUNT).map(Action::ActivitySlot));
+        result.extend_from_slice(&[
+            Action::IncreaseWorkTime,
+            Action::DecreaseWorkTime,
+            Action::SelectPrevious,
+            Action::SelectNext,
+            Action::ToggleReclassify,
+            Action::SplitInterval,
+            Action::PreviousDay,
+            Action::NextDay,
+            Action::ToggleCalendar,
+            Action::EditDay,
+            Action::ToggleHelp,
+            Action::Quit,
+        ]);
+        result
+    }
+
+    /// The name used for the `key_<name>` settings in the config file
+    pub fn name(&self) -> String {
+        match self {
+            Action::ToggleWork => "toggle_work".to_owned(),
+            Action::ActivitySlot(index) => format!("activity_{}", index + 1),
+            Action::IncreaseWorkTime => "increase_work_time".to_owned(),
+            Action::DecreaseWorkTime => "decrease_work_time".to_owned(),
+            Action::SelectPrevious => "select_previous".to_owned(),
+            Action::SelectNext => "select_next".to_owned(),
+            Action::ToggleReclassify => "reclassify".to_owned(),
+            Action::SplitInterval => "split_interval".to_owned(),
+            Action::PreviousDay => "previous_day".to_owned(),
+            Action::NextDay => "next_day".to_owned(),
+            Action::ToggleCalendar => "calendar".to_owned(),
+            Action::EditDay => "edit_day".to_owned(),
+            Action::ToggleHelp => "help".to_owned(),
+            Action::Quit => "quit".to_owned(),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::all()
+            .into_iter()
+            .find(|action| action.name() == name)
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Action::ToggleWork => "Begin work / take a break".to_owned(),
+            Action::ActivitySlot(index) => format!("Begin/stop activity in row {}", index + 1),
+            Action::IncreaseWorkTime => "Increase preferred work time".to_owned(),
+            Action::DecreaseWorkTime => "Decrease preferred work time".to_owned(),
+            Action::SelectPrevious => "Select previous interval / scroll up".to_owned(),
+            Action::SelectNext => "Select next interval / scroll down".to_owned(),
+            Action::ToggleReclassify => "Open/close assigning non-specific work".to_owned(),
+            Action::SplitInterval => "Split selected non-specific interval".to_owned(),
+            Action::PreviousDay => "Show previous day".to_owned(),
+            Action::NextDay => "Show next day".to_owned(),
+            Action::ToggleCalendar => "Open/close calendar".to_owned(),
+            Action::EditDay => "Edit shown past day (press twice)".to_owned(),
+            Action::ToggleHelp => "Show/hide this help".to_owned(),
+            Action::Quit => "Quit".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> KeyBinding {
+        // NOTE: Terminals report shifted characters differently (i.e. '+' with or without the
+        //       shift modifier) so we ignore shift for characters and compare the character
+        //       itself instead
+        if let KeyCode::Char(character) = code {
+            if modifiers.contains(KeyModifiers::SHIFT) {
+                return KeyBinding {
+                    code: KeyCode::Char(character.to_uppercase().next().unwrap_or(character)),
+                    modifiers: modifiers - KeyModifiers::SHIFT,
+                };
+            }
+        }
+        KeyBinding { code, modifiers }
+    }
+
+    /// Parses keys like `x`, `+`, `esc`, `f5`, `ctrl+c` or `alt+shift+left`
+    pub fn from_string(input: &str) -> Option<KeyBinding> {
+        let input = input.trim();
+        // NOTE: We split at the last '+' that is not the last character so that '+' and
+        //       'ctrl++' can be bound too
+        let last_character_start = input
+            .char_indices()
+            .last()
+            .map(|(position, _character)| position)
+            .unwrap_or(0);
+        let (modifier_names, key_name) = match input[..last_character_start].rfind('+') {
+            Some(position) => (&input[..position], &input[position + 1..]),
+            None => ("", input),
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier_name in modifier_names.split('+').filter(|name| !name.is_empty()) {
+            modifiers |= match modifier_name.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+        }
+
+        let mut characters = key_name.chars();
+        let code = match (characters.next(), characters.next()) {
+            (Some(character), None) => KeyCode::Char(character),
+            _ => match key_name.to_lowercase().as_str() {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "space" => KeyCode::Char(' '),
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                name => {
+                    let number: u8 = name.strip_prefix('f')?.parse().ok()?;
+                    if !(1..=12).contains(&number) {
+                        return None;
+                    }
+                    KeyCode::F(number)
+                }
+            },
+        };
+
+        Some(KeyBinding::new(code, modifiers))
+    }
+
+    pub fn to_string(self) -> String {
+        let mut result = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            result += "ctrl+";
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            result += "alt+";
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            result += "shift+";
+        }
+        result += &match self.code {
+            KeyCode::Char(' ') => "space".to_owned(),
+            KeyCode::Char(character) => character.to_string(),
+            KeyCode::F(number) => format!("f{}", number),
+            KeyCode::Esc => "esc".to_owned(),
+            KeyCode::Enter => "enter".to_owned(),
+            KeyCode::Tab => "tab".to_owned(),
+            KeyCode::Backspace => "backspace".to_owned(),
+            KeyCode::Delete => "delete".to_owned(),
+            KeyCode::Insert => "insert".to_owned(),
+            KeyCode::Home => "home".to_owned(),
+            KeyCode::End => "end".to_owned(),
+            KeyCode::PageUp => "pageup".to_owned(),
+            KeyCode::PageDown => "pagedown".to_owned(),
+            KeyCode::Up => "up".to_owned(),
+            KeyCode::Down => "down".to_owned(),
+            KeyCode::Left => "left".to_owned(),
+            KeyCode::Right => "right".to_owned(),
+            other => format!("{:?}", other).to_lowercase(),
+        };
+        result
+    }
+}
+
+/// Maps keys to actions. Every action can have multiple keys bound to it.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<KeyBinding>)>,
+}
+
+impl Keymap {
+    pub fn default() -> Keymap {
+        let key = |input: &str| KeyBinding::from_string(input).unwrap();
+        let mut bindings = vec![(Action::ToggleWork, vec![key("x")])];
+        for index in 0..ACTIVITY_LIST_VISIBLE_COUNT {
+            bindings.push((
+                Action::ActivitySlot(index),
+                vec![key(&(index + 1).to_string())],
+            ));
+        }
+        bindings.extend(vec![
+            (Action::IncreaseWorkTime, vec![key("+")]),
+            (Action::DecreaseWorkTime, vec![key("-")]),
+            (Action::SelectPrevious, vec![key("up"), key("k")]),
+            (Action::SelectNext, vec![key("down"), key("j")]),
+            (Action::ToggleReclassify, vec![key("r")]),
+            (Action::SplitInterval, vec![key("s")]),
+            (Action::PreviousDay, vec![key("left"), key("h")]),
+            (Action::NextDay, vec![key("right"), key("l")]),
+            (Action::ToggleCalendar, vec![key("c")]),
+            (Action::EditDay, vec![key("e")]),
+            (Action::ToggleHelp, vec![key("?")]),
+            (Action::Quit, vec![key("esc"), key("ctrl+c")]),
+        ]);
+        Keymap { bindings }
+    }
+
+    /// Replaces the keys of the given action with the comma separated list of keys in `value`
+    pub fn rebind(&mut self, action: Action, value: &str) -> Option<()> {
+        let keys: Option<Vec<KeyBinding>> = value
+            .split(',')
+            .filter(|key| !key.trim().is_empty())
+            .map(KeyBinding::from_string)
+            .collect();
+        let keys = keys?;
+        for (bound_action, bound_keys) in self.bindings.iter_mut() {
+            if *bound_action == action {
+                *bound_keys = keys;
+                return Some(());
+            }
+        }
+        None
+    }
+
+    /// Returns a key that is bound to two different actions together with both actions
+    pub fn find_conflict(&self) -> Option<(KeyBinding, Action, Action)> {
+        for (index, (action, keys)) in self.bindings.iter().enumerate() {
+            for (other_action, other_keys) in &self.bindings[index + 1..] {
+                if let Some(key) = keys.iter().find(|key| other_keys.contains(key)) {
+                    return Some((*key, *action, *other_action));
+                }
+            }
+        }
+        None
+    }
+
+    pub fn action_for_key_event(&self, event: &KeyEvent) -> Option<Action> {
+        let pressed = KeyBinding::new(event.code, event.modifiers);
+        self.bindings
+            .iter()
+            .find(|(_action, keys)| keys.contains(&pressed))
+            .map(|(action, _keys)| *action)
+    }
+
+    /// Returns the first key bound to the given action for showing it in key hints
+    pub fn key_hint(&self, action: Action) -> String {
+        self.keys_for_action(action)
+            .first()
+            .map(|key| key.to_string())
+            .unwrap_or_else(|| "-".to_owned())
+    }
+
+    fn keys_for_action(&self, action: Action) -> &[KeyBinding] {
+        self.bindings
+            .iter()
+            .find(|(bound_action, _keys)| *bound_action == action)
+            .map(|(_action, keys)| keys.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Returns pairs of (keys, description) for all actions
+    pub fn describe_bindings(&self) -> Vec<(String, String)> {
+        self.bindings
+            .iter()
+            .map(|(action, keys)| {
+                let keys = if keys.is_empty() {
+                    "(unbound)".to_owned()
+                } else {
+                    keys.iter()
+                        .map(|key| key.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                (keys, action.description())
+            })
+            .collect()
+    }
+}
@@ -1,69 +1,296 @@
-mod dayentry;
-mod time;
+// NOTE: Types are formatted by inherent `to_string` methods next to their `from_string`
+//       counterparts instead of `Display` implementations
+#![allow(clippy::inherent_to_string)]
 
-use dayentry::{
-    write_durations_summary, DayEntry, ACTIVITY_NAME_LEAVE, ACTIVITY_NAME_NON_SPECIFIC_WORK,
-};
-use time::{DateTimeHelper, TimeDuration, TimeStamp};
+mod activitylist;
+mod commands;
+mod config;
+mod history;
+mod keymap;
+mod profile;
+mod reclassify;
+mod screen;
+mod theme;
+mod timeline;
+mod watcher;
+
+use activitylist::{load_activity_list, reload_activity_list, ACTIVITY_LIST_FILEPATH};
+use config::{Config, CONFIG_FILEPATH};
+use history::HistoryScreen;
+use keymap::{Action, Keymap};
+use reclassify::ReclassifyScreen;
+use screen::ScreenBuffer;
+use theme::Theme;
+use timeline::create_timeline_screen;
+use watcher::FileWatcher;
 
-use ct_lib_core::path_exists;
+use thyme_core::budget::Period;
+use thyme_core::dayentry::{
+    write_durations_summary, ACTIVITY_NAME_LEAVE, ACTIVITY_NAME_NON_SPECIFIC_WORK,
+};
+use thyme_core::goals::GoalKind;
+use thyme_core::indexmap::IndexMap;
+use thyme_core::storage::TODAY_TIMESHEET_FILEPATH;
+use thyme_core::time::{TimeDuration, TimeStamp};
+use thyme_core::{ActivityListEntry, DayEntry, RealClock};
 
-use chrono::{prelude::*, Local};
+use chrono::prelude::*;
 use crossterm::{
-    cursor::{self},
-    event::{KeyCode, KeyEvent, KeyModifiers},
-    style::Print,
+    event::{DisableMouseCapture, EnableMouseCapture, MouseButton, MouseEvent, MouseEventKind},
+    style::ContentStyle,
     terminal::{DisableLineWrap, EnableLineWrap, SetTitle},
     ExecutableCommand, QueueableCommand,
 };
 
 use std::fmt::Write;
 
+/// Number of activities that are shown at once and can be selected via the activity keys
+const ACTIVITY_LIST_VISIBLE_COUNT: usize = 9;
+const GOAL_PROGRESS_BAR_WIDTH: usize = 10;
+
+enum Selection {
+    /// Begins work if we are on a break and takes a break otherwise
+    ToggleWork,
+    /// Starts or stops the activity with the given index in the activity list
+    Activity(usize),
+}
+
+/// Rows of the main screen that react to mouse clicks
+#[derive(Debug, Default)]
+struct ClickableRows {
+    toggle_work: Option<usize>,
+    /// Pairs of (row, index in activity list)
+    activities: Vec<(usize, usize)>,
+}
+
+impl ClickableRows {
+    fn selection_at(&self, row: usize) -> Option<Selection> {
+        if self.toggle_work == Some(row) {
+            return Some(Selection::ToggleWork);
+        }
+        self.activities
+            .iter()
+            .find(|(activity_row, _index)| *activity_row == row)
+            .map(|(_row, index)| Selection::Activity(*index))
+    }
+}
+
+/// Writes the reports of the given day and its week. Returns the first error that occurred.
+fn write_reports(
+    day_entry: &DayEntry,
+    config: &Config,
+    activity_list: &[ActivityListEntry],
+) -> Result<(), String> {
+    // NOTE: The week report needs to load the other days of the week. A broken timesheet of
+    //       another day must not keep us from writing the report of this day.
+    let day_result = day_entry.write_day_reports(&config.report, activity_list);
+    let week_result = day_entry.write_week_report(&config.report, activity_list);
+    day_result.and(week_result)
+}
+
+/// Writes the timesheet and the reports of the given day. The timesheet is not written while
+/// today's timesheet is broken so that the broken edit can still be fixed.
+fn write_timesheet_and_reports(
+    day_entry: &mut DayEntry,
+    config: &Config,
+    activity_list: &[ActivityListEntry],
+    is_timesheet_broken: bool,
+) -> Result<(), String> {
+    if !is_timesheet_broken {
+        day_entry
+            .write_back()
+            .unwrap_or_else(|error| panic!("{}", error));
+    }
+    write_reports(day_entry, config, activity_list)
+}
+
+/// Reloads the activity durations of the previous days of the week. If that fails the previous
+/// durations are kept and the error is returned.
+fn reload_week_durations(
+    day_entry: &DayEntry,
+    week_durations: &mut IndexMap<String, TimeDuration>,
+) -> Option<String> {
+    match day_entry.load_week_durations_before() {
+        Ok(reloaded) => {
+            *week_durations = reloaded;
+            None
+        }
+        Err(error) => Some(error),
+    }
+}
+
 fn main() -> crossterm::Result<()> {
     ct_lib_core::panic_set_hook_wait_for_keypress();
 
-    let mut day_entry = DayEntry::load_or_create();
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let profile = match profile::take_profile_argument(&mut args) {
+        Ok(profile) => profile,
+        Err(error) => {
+            eprintln!("Error: {}\n\n{}", error, commands::usage());
+            std::process::exit(1);
+        }
+    };
+    let launch_directory = profile::enter_data_directory(profile.as_deref());
+
+    if !args.is_empty() {
+        if let Err(error) = commands::run_command(&args, &launch_directory) {
+            eprintln!("Error: {}\n\n{}", error, commands::usage());
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let mut config = Config::reload();
+    let clock = RealClock::shared();
+    let storage = config
+        .create_storage()
+        .unwrap_or_else(|error| panic!("{}", error));
+    let mut day_entry =
+        DayEntry::load_or_create(&clock, &storage).unwrap_or_else(|error| panic!("{}", error));
+    let mut activity_list = reload_activity_list();
+    // NOTE: Reports are written again every minute, so we show why writing them failed until
+    //       the next write succeeds
+    let mut report_error = write_reports(&day_entry, &config, &activity_list).err();
+
+    let mut file_watcher = FileWatcher::new(
+        TODAY_TIMESHEET_FILEPATH,
+        ACTIVITY_LIST_FILEPATH,
+        CONFIG_FILEPATH,
+    );
 
     let mut stdout = std::io::stdout();
     crossterm::terminal::enable_raw_mode()?;
     stdout.execute(DisableLineWrap)?;
+    stdout.execute(EnableMouseCapture)?;
 
-    let mut preferred_working_time = TimeDuration { minutes: 8 * 60 };
-    let mut previous_time = time::get_current_datetime();
+    let mut preferred_working_time = config.preferred_working_time;
+    let mut previous_time = clock.now();
+    let mut previous_screen: Option<ScreenBuffer> = None;
+    let mut activity_list_scroll_offset = 0;
+    let mut is_help_shown = false;
+    let mut reclassify_screen: Option<ReclassifyScreen> = None;
+    let mut history_screen: Option<HistoryScreen> = None;
+    // NOTE: A broken config or activity list must not end the program while it is edited, so we
+    //       keep the previous one and show the error until the file is fixed
+    let mut config_error: Option<String> = None;
+    let mut activity_list_error: Option<String> = None;
+    // NOTE: The same goes for today's timesheet. We keep the day in memory and don't write it back
+    //       until the timesheet can be loaded again.
+    let mut timesheet_error: Option<String> = None;
+    // NOTE: We only reload the previous days of the week from time to time as they rarely change
+    let mut week_activity_durations_before = IndexMap::new();
+    let mut week_error = reload_week_durations(&day_entry, &mut week_activity_durations_before);
     let mut is_running = true;
     while is_running {
-        let activity_names_list = reload_activity_names();
-
-        day_entry.hotreload_external_changes();
+        let file_changes = file_watcher.poll_changes();
+        if file_changes.activity_list {
+            match load_activity_list() {
+                Ok(reloaded) => {
+                    activity_list = reloaded;
+                    activity_list_error = None;
+                }
+                Err(error) => activity_list_error = Some(error),
+            }
+        }
+        if file_changes.config {
+            match Config::load() {
+                Ok(reloaded) => {
+                    config = reloaded;
+                    preferred_working_time = config.preferred_working_time;
+                    config_error = None;
+                }
+                Err(error) => config_error = Some(error),
+            }
+        }
+        if file_changes.timesheet {
+            match day_entry.hotreload_external_changes() {
+                Ok(is_reloaded) => {
+                    timesheet_error = None;
+                    if is_reloaded {
+                        report_error = write_reports(&day_entry, &config, &activity_list).err();
+                    }
+                }
+                Err(error) => timesheet_error = Some(error),
+            }
+        }
 
         // Write changes every minute
-        let current_time = time::get_current_datetime();
+        let current_time = clock.now();
         if (current_time - previous_time).num_minutes() > 0 {
             // One minute has passed
-            day_entry.write_back();
+            let mut previous_day_result = Ok(());
             if current_time.date() != previous_time.date() {
-                // A whole day has passed - we need to create a new entry
-                day_entry = DayEntry::create_empty();
+                // A whole day has passed - we need to close the previous day and create a new
+                // entry
+                day_entry.leave_at_end_of_day();
+                // NOTE: The previous day is only written to the database and not to today's
+                //       timesheet, which the new day replaces anyway
+                previous_day_result =
+                    write_timesheet_and_reports(&mut day_entry, &config, &activity_list, false);
+                day_entry = DayEntry::create_empty(&clock, &storage)
+                    .unwrap_or_else(|error| panic!("{}", error));
+                timesheet_error = None;
             }
+            let result = write_timesheet_and_reports(
+                &mut day_entry,
+                &config,
+                &activity_list,
+                timesheet_error.is_some(),
+            );
+            report_error = previous_day_result.and(result).err();
+            week_error = reload_week_durations(&day_entry, &mut week_activity_durations_before);
             previous_time = current_time;
         }
 
         let (terminal_width, terminal_height) = crossterm::terminal::size().unwrap_or((100, 30));
-        let terminal_width = (terminal_width - 2) as usize;
-        let terminal_height = (terminal_height.min(30) - 2) as usize;
-
-        let clear_screen = create_clear_screen(terminal_width, terminal_height);
-        let sprite_screen = create_sprite_screen(&day_entry, terminal_width, terminal_height);
-        let main_screen = create_main_screen(
-            &day_entry,
-            &activity_names_list,
-            preferred_working_time,
-            terminal_width,
-            terminal_height,
+        // NOTE: We leave the last column empty as some terminals scroll when writing to the
+        //       bottom-right cell
+        let terminal_width = terminal_width.saturating_sub(1) as usize;
+        let terminal_height = terminal_height as usize;
+
+        let sprite_screen =
+            create_sprite_screen(&day_entry, &config.theme, terminal_width, terminal_height);
+        activity_list_scroll_offset = usize::min(
+            activity_list_scroll_offset,
+            activity_list
+                .len()
+                .saturating_sub(ACTIVITY_LIST_VISIBLE_COUNT),
         );
+        let (main_screen, clickable_rows) = match (&reclassify_screen, &history_screen) {
+            (_, Some(history_screen)) => (
+                history_screen.render(
+                    &activity_list,
+                    activity_list_scroll_offset,
+                    &config,
+                    terminal_width,
+                ),
+                ClickableRows::default(),
+            ),
+            (Some(reclassify_screen), None) => (
+                reclassify_screen.render(
+                    &day_entry,
+                    &activity_list,
+                    activity_list_scroll_offset,
+                    &config,
+                ),
+                ClickableRows::default(),
+            ),
+            (None, None) => create_main_screen(
+                &day_entry,
+                profile.as_deref(),
+                &activity_list,
+                &week_activity_durations_before,
+                activity_list_scroll_offset,
+                &config,
+                preferred_working_time,
+                terminal_width,
+                terminal_height,
+            ),
+        };
 
         let title = {
-            let blink = Local::now().second() % 2 == 0;
+            #[allow(clippy::manual_is_multiple_of)]
+            let blink = clock.now().second() % 2 == 0;
             if day_entry.get_current_activity().is_some() {
                 if day_entry.is_currently_working() {
                     format!(
@@ -83,92 +310,232 @@ fn main() -> crossterm::Result<()> {
                     )
                 }
             } else {
-                format!("Not Checked in today")
+                "Not Checked in today".to_owned()
             }
         };
+        let title = match &profile {
+            Some(profile) => format!("[{}] {}", profile, title),
+            None => title,
+        };
+
+        let mut screen = ScreenBuffer::new(terminal_width, terminal_height);
+        if reclassify_screen.is_none() && history_screen.is_none() {
+            screen.draw_text(0, 0, &sprite_screen);
+        }
+        screen.draw_text(0, 0, &main_screen);
+        let mut errors: Vec<String> = config_error
+            .iter()
+            .chain(activity_list_error.iter())
+            .map(|error| format!("{} (keeping the previous settings)", error))
+            .chain(
+                timesheet_error
+                    .iter()
+                    .map(|error| format!("{} (keeping the timesheet in memory)", error)),
+            )
+            .chain(report_error.iter().cloned())
+            .chain(week_error.iter().cloned())
+            .collect();
+        // NOTE: The reports and the week durations usually fail because of the same timesheet
+        errors.dedup();
+        for (index, error) in errors.iter().enumerate() {
+            screen.draw_text(
+                0,
+                terminal_height.saturating_sub(errors.len() - index),
+                &config
+                    .theme
+                    .paint(config.theme.overtime, &format!("Error: {}", error)),
+            );
+        }
+        if is_help_shown {
+            screen.draw_text(2, 1, &create_help_screen(&config.keymap, &config.theme));
+        }
 
         use std::io::Write;
-        stdout
-            .queue(SetTitle(&title))?
-            .queue(cursor::MoveTo(0, 0))?
-            //.queue(Clear(ClearType::All))? // This just scrolls down on Windows and looks glitchy
-            .queue(Print(&clear_screen))?
-            .queue(cursor::MoveTo(0, 0))?
-            .queue(Print(&sprite_screen))?
-            .queue(cursor::MoveTo(0, 0))?
-            .queue(Print(&main_screen))?;
+        stdout.queue(SetTitle(&title))?;
+        // NOTE: We don't use `Clear(ClearType::All)` as this just scrolls down on Windows and
+        //       looks glitchy. Instead only the cells that changed since the last frame are
+        //       redrawn.
+        screen.queue_changes(previous_screen.as_ref(), &mut stdout)?;
         stdout.flush()?;
+        previous_screen = Some(screen);
 
         // Using `poll` for non-blocking read
         if crossterm::event::poll(std::time::Duration::from_millis(1000))? {
             let selection = match crossterm::event::read()? {
-                crossterm::event::Event::Key(KeyEvent {
-                    code: KeyCode::Char('1'),
-                    modifiers: KeyModifiers::NONE,
-                }) => Some(1),
-                crossterm::event::Event::Key(KeyEvent {
-                    code: KeyCode::Char('2'),
-                    modifiers: KeyModifiers::NONE,
-                }) => Some(2),
-                crossterm::event::Event::Key(KeyEvent {
-                    code: KeyCode::Char('3'),
-                    modifiers: KeyModifiers::NONE,
-                }) => Some(3),
-                crossterm::event::Event::Key(KeyEvent {
-                    code: KeyCode::Char('4'),
-                    modifiers: KeyModifiers::NONE,
-                }) => Some(4),
-                crossterm::event::Event::Key(KeyEvent {
-                    code: KeyCode::Char('5'),
-                    modifiers: KeyModifiers::NONE,
-                }) => Some(5),
-                crossterm::event::Event::Key(KeyEvent {
-                    code: KeyCode::Char('6'),
-                    modifiers: KeyModifiers::NONE,
-                }) => Some(6),
-                crossterm::event::Event::Key(KeyEvent {
-                    code: KeyCode::Char('7'),
-                    modifiers: KeyModifiers::NONE,
-                }) => Some(7),
-                crossterm::event::Event::Key(KeyEvent {
-                    code: KeyCode::Char('8'),
-                    modifiers: KeyModifiers::NONE,
-                }) => Some(8),
-                crossterm::event::Event::Key(KeyEvent {
-                    code: KeyCode::Char('9'),
-                    modifiers: KeyModifiers::NONE,
-                }) => Some(9),
-                crossterm::event::Event::Key(KeyEvent {
-                    code: KeyCode::Char('x'),
-                    modifiers: KeyModifiers::NONE,
-                }) => Some(0),
-
-                crossterm::event::Event::Key(KeyEvent {
-                    code: KeyCode::Char('+'),
-                    modifiers: KeyModifiers::NONE,
+                crossterm::event::Event::Key(_) if is_help_shown => {
+                    // Any key closes the help
+                    is_help_shown = false;
+                    None
+                }
+                crossterm::event::Event::Key(key_event) if reclassify_screen.is_some() => {
+                    let screen = reclassify_screen.as_mut().unwrap();
+                    match config.keymap.action_for_key_event(&key_event) {
+                        Some(Action::ActivitySlot(index)) => {
+                            if let Some(activity) =
+                                activity_list.get(activity_list_scroll_offset + index)
+                            {
+                                screen.assign(
+                                    &mut day_entry,
+                                    &activity.name,
+                                    &config,
+                                    &activity_list,
+                                );
+                            }
+                        }
+                        Some(Action::SelectPrevious) => screen.select_previous(),
+                        Some(Action::SelectNext) => screen.select_next(&day_entry),
+                        Some(Action::SplitInterval) => screen.toggle_split(&day_entry),
+                        Some(Action::IncreaseWorkTime) => screen.move_split(&day_entry, 5),
+                        Some(Action::DecreaseWorkTime) => screen.move_split(&day_entry, -5),
+                        Some(Action::ToggleHelp) => is_help_shown = true,
+                        Some(Action::ToggleReclassify) | Some(Action::Quit) => {
+                            reclassify_screen = None;
+                            week_error = reload_week_durations(
+                                &day_entry,
+                                &mut week_activity_durations_before,
+                            );
+                        }
+                        Some(Action::ToggleWork)
+                        | Some(Action::PreviousDay)
+                        | Some(Action::NextDay)
+                        | Some(Action::ToggleCalendar)
+                        | Some(Action::EditDay)
+                        | None => {}
+                    }
+                    None
+                }
+                crossterm::event::Event::Key(key_event) if history_screen.is_some() => {
+                    let screen = history_screen.as_mut().unwrap();
+                    let action = config.keymap.action_for_key_event(&key_event);
+                    if action != Some(Action::EditDay) {
+                        screen.cancel_edit_request();
+                    }
+                    let is_screen_open = match action {
+                        Some(Action::ActivitySlot(index)) => {
+                            if let Some(activity) =
+                                activity_list.get(activity_list_scroll_offset + index)
+                            {
+                                screen.assign(&activity.name, &config, &activity_list);
+                            }
+                            true
+                        }
+                        Some(Action::PreviousDay) => screen.move_date(-1),
+                        Some(Action::NextDay) => screen.move_date(1),
+                        Some(Action::SelectPrevious) => {
+                            screen.select_previous();
+                            true
+                        }
+                        Some(Action::SelectNext) => {
+                            screen.select_next();
+                            true
+                        }
+                        Some(Action::ToggleCalendar) => screen.toggle_calendar(),
+                        Some(Action::EditDay) => {
+                            screen.request_editing();
+                            true
+                        }
+                        Some(Action::ToggleHelp) => {
+                            is_help_shown = true;
+                            true
+                        }
+                        Some(Action::Quit) => false,
+                        Some(Action::ToggleWork)
+                        | Some(Action::ToggleReclassify)
+                        | Some(Action::SplitInterval)
+                        | Some(Action::IncreaseWorkTime)
+                        | Some(Action::DecreaseWorkTime)
+                        | None => true,
+                    };
+                    if !is_screen_open {
+                        history_screen = None;
+                        // NOTE: Previous days of the week may have been edited
+                        week_error =
+                            reload_week_durations(&day_entry, &mut week_activity_durations_before);
+                    }
+                    None
+                }
+                crossterm::event::Event::Key(key_event) => {
+                    match config.keymap.action_for_key_event(&key_event) {
+                        Some(Action::ToggleWork) => Some(Selection::ToggleWork),
+                        Some(Action::ActivitySlot(index)) => {
+                            Some(Selection::Activity(activity_list_scroll_offset + index))
+                        }
+                        Some(Action::IncreaseWorkTime) => {
+                            preferred_working_time.minutes =
+                                i32::min(preferred_working_time.minutes + 15, 10 * 60);
+                            None
+                        }
+                        Some(Action::DecreaseWorkTime) => {
+                            preferred_working_time.minutes =
+                                i32::max(preferred_working_time.minutes - 15, 4 * 60);
+                            None
+                        }
+                        Some(Action::SelectPrevious) => {
+                            activity_list_scroll_offset =
+                                activity_list_scroll_offset.saturating_sub(1);
+                            None
+                        }
+                        Some(Action::SelectNext) => {
+                            activity_list_scroll_offset += 1;
+                            None
+                        }
+                        Some(Action::ToggleReclassify) => {
+                            reclassify_screen = Some(ReclassifyScreen::new(&day_entry));
+                            None
+                        }
+                        Some(Action::ToggleHelp) => {
+                            is_help_shown = true;
+                            None
+                        }
+                        Some(Action::Quit) => {
+                            is_running = false;
+                            None
+                        }
+                        Some(Action::PreviousDay) => {
+                            history_screen = Some(HistoryScreen::new(&day_entry, false));
+                            None
+                        }
+                        Some(Action::ToggleCalendar) => {
+                            history_screen = Some(HistoryScreen::new(&day_entry, true));
+                            None
+                        }
+                        Some(Action::NextDay)
+                        | Some(Action::EditDay)
+                        | Some(Action::SplitInterval)
+                        | None => None,
+                    }
+                }
+
+                crossterm::event::Event::Mouse(MouseEvent {
+                    kind: MouseEventKind::Down(MouseButton::Left),
+                    row,
+                    ..
                 }) => {
-                    preferred_working_time.minutes =
-                        i32::min(preferred_working_time.minutes + 15, 10 * 60);
+                    if is_help_shown {
+                        is_help_shown = false;
+                        None
+                    } else {
+                        clickable_rows.selection_at(row as usize)
+                    }
+                }
+                crossterm::event::Event::Mouse(MouseEvent {
+                    kind: MouseEventKind::ScrollUp,
+                    ..
+                }) => {
+                    activity_list_scroll_offset = activity_list_scroll_offset.saturating_sub(1);
                     None
                 }
-                crossterm::event::Event::Key(KeyEvent {
-                    code: KeyCode::Char('-'),
-                    modifiers: KeyModifiers::NONE,
+                crossterm::event::Event::Mouse(MouseEvent {
+                    kind: MouseEventKind::ScrollDown,
+                    ..
                 }) => {
-                    preferred_working_time.minutes =
-                        i32::max(preferred_working_time.minutes - 15, 4 * 60);
+                    activity_list_scroll_offset += 1;
                     None
                 }
 
-                crossterm::event::Event::Key(KeyEvent {
-                    code: KeyCode::Char('c'),
-                    modifiers: KeyModifiers::CONTROL,
-                })
-                | crossterm::event::Event::Key(KeyEvent {
-                    code: KeyCode::Esc,
-                    modifiers: KeyModifiers::NONE,
-                }) => {
-                    is_running = false;
+                crossterm::event::Event::Resize(_, _) => {
+                    // Force a full redraw
+                    previous_screen = None;
                     None
                 }
                 _ => None,
@@ -176,93 +543,52 @@ fn main() -> crossterm::Result<()> {
 
             // Run an activity
             if let Some(selection) = selection {
-                if selection == 0 {
-                    if day_entry.is_currently_working() {
-                        day_entry.start_activitiy(ACTIVITY_NAME_LEAVE, false);
-                    } else {
-                        day_entry.start_activitiy(ACTIVITY_NAME_NON_SPECIFIC_WORK, true);
-                    }
-                } else {
-                    let index = selection - 1;
-                    if index < activity_names_list.len() {
-                        let activity_name = &activity_names_list[index];
-                        let is_active = day_entry
-                            .get_current_activity()
-                            .map(|activity| activity.name == *activity_name)
-                            .unwrap_or(false);
-
-                        if is_active {
-                            day_entry.start_activitiy(ACTIVITY_NAME_NON_SPECIFIC_WORK, true);
+                match selection {
+                    Selection::ToggleWork => {
+                        if day_entry.is_currently_working() {
+                            day_entry.start_activitiy(ACTIVITY_NAME_LEAVE, false);
                         } else {
-                            day_entry.start_activitiy(activity_name, true);
+                            day_entry.start_activitiy(ACTIVITY_NAME_NON_SPECIFIC_WORK, true);
+                        }
+                    }
+                    Selection::Activity(index) => {
+                        if index < activity_list.len() {
+                            let activity_name = &activity_list[index].name;
+                            let is_active = day_entry
+                                .get_current_activity()
+                                .map(|activity| activity.name == *activity_name)
+                                .unwrap_or(false);
+
+                            if is_active {
+                                day_entry.start_activitiy(ACTIVITY_NAME_NON_SPECIFIC_WORK, true);
+                            } else {
+                                day_entry.start_activitiy(activity_name, true);
+                            }
                         }
                     }
                 }
                 // Something changed
-                day_entry.write_back();
+                report_error = write_timesheet_and_reports(
+                    &mut day_entry,
+                    &config,
+                    &activity_list,
+                    timesheet_error.is_some(),
+                )
+                .err();
             }
         }
     }
 
+    stdout.execute(DisableMouseCapture)?;
     stdout.execute(EnableLineWrap)?;
     crossterm::terminal::disable_raw_mode()?;
 
     Ok(())
 }
 
-fn reload_activity_names() -> Vec<String> {
-    const ACTIVITY_NAMES_FILEPATH: &str = "activity_names.txt";
-
-    // Auto-rename old project names file name
-    if path_exists("project_names.txt") {
-        std::fs::rename("project_names.txt", ACTIVITY_NAMES_FILEPATH)
-            .expect("Could not rename old 'project_names.txt' to 'activity_names.txt'");
-    }
-
-    if !path_exists(ACTIVITY_NAMES_FILEPATH) {
-        let exampletext = format!(
-            "Welcome to Thyme! :)
-You can add your own activity names here
-by modifying '{}'!
-Each activity name will be its own line in '{}'.
-Currently only up to 9 activity names are supported.
-Why not try out modifying '{}' now? 
-(You don't need to close Thyme for this)
-I will be waiting here",
-            ACTIVITY_NAMES_FILEPATH, ACTIVITY_NAMES_FILEPATH, ACTIVITY_NAMES_FILEPATH
-        );
-        std::fs::write(&ACTIVITY_NAMES_FILEPATH, &exampletext).unwrap_or_else(|error| {
-            panic!(
-                "Could not write to '{}' - {}",
-                &ACTIVITY_NAMES_FILEPATH, error
-            )
-        });
-    }
-
-    let actitivities: Vec<String> = std::fs::read_to_string(ACTIVITY_NAMES_FILEPATH)
-        .unwrap_or_else(|error| panic!("Could not read '{}' - {}", &ACTIVITY_NAMES_FILEPATH, error))
-        .lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| {
-            assert!(
-                line.len() <= 70,
-                "Activity name [{}] is too long - please make it shorter than 70 character",
-                line
-            );
-            line
-        })
-        .map(|line| line.to_owned())
-        .collect();
-
-    assert!(
-        actitivities.len() < 10,
-        "Activitiy list is too long, only up to 9 activities are supported"
-    );
-    actitivities
-}
-
 fn create_sprite_screen(
     day_entry: &DayEntry,
+    theme: &Theme,
     _terminal_width: usize,
     _terminal_height: usize,
 ) -> String {
@@ -304,7 +630,7 @@ Z..............Z
 .ZZZZZZZZZZZZZZ.",
     ];
 
-    let sprite_index = (Local::now().second() % 2) as usize;
+    let sprite_index = (day_entry.clock.now().second() % 2) as usize;
     let sprite = if day_entry.is_currently_working() {
         SPRITE_WORKING[sprite_index].to_owned()
     } else {
@@ -323,44 +649,55 @@ Z..............Z
     if day_entry.get_leave_duration().is_some() {
         writeln!(result, "(Don't forget to log your hours!)",).unwrap();
     } else {
-        writeln!(result, "").unwrap();
+        writeln!(result).unwrap();
     }
 
+    let sprite_style = if day_entry.is_currently_working() {
+        ContentStyle::new()
+    } else {
+        theme.dimmed
+    };
     for line in sprite.lines() {
         for _ in 0..padding_left {
             write!(result, " ").unwrap();
         }
-        writeln!(result, "{}", line).unwrap();
-    }
-    result
-}
-
-fn create_clear_screen(terminal_width: usize, terminal_height: usize) -> String {
-    let mut result = String::new();
-    for _line_index in 0..terminal_height {
-        for _col_index in 0..terminal_width {
-            write!(result, " ").unwrap();
-        }
-        writeln!(result, "").unwrap();
+        writeln!(result, "{}", theme.paint(sprite_style, line)).unwrap();
     }
     result
 }
 
+/// Returns the rendered screen and the rows that can be clicked on. The activity list shows
+/// up to `ACTIVITY_LIST_VISIBLE_COUNT` activities beginning at `activity_list_scroll_offset`.
+#[allow(clippy::too_many_arguments)]
 fn create_main_screen(
     day_entry: &DayEntry,
-    activity_names_list: &[String],
+    profile: Option<&str>,
+    activity_list: &[ActivityListEntry],
+    week_activity_durations_before: &IndexMap<String, TimeDuration>,
+    activity_list_scroll_offset: usize,
+    config: &Config,
     preferred_working_time: TimeDuration,
-    _terminal_width: usize,
+    terminal_width: usize,
     _terminal_heigth: usize,
-) -> String {
+) -> (String, ClickableRows) {
+    let theme = &config.theme;
+    let keymap = &config.keymap;
     let mut result = String::new();
+    let mut clickable_rows = ClickableRows::default();
+    let current_row = |result: &String| result.matches('\n').count();
 
     write!(
         result,
-        "Today is {} -- ",
-        day_entry.date.format("%A %e. %b (%d.%m.%Y)"),
+        "{} -- ",
+        theme.paint(
+            theme.heading,
+            &format!("Today is {}", day_entry.date.format("%A %e. %b (%d.%m.%Y)"))
+        ),
     )
     .unwrap();
+    if let Some(profile) = profile {
+        write!(result, "[{}] -- ", profile).unwrap();
+    }
 
     if let Some(checkin_time) = day_entry.first_checkin_time() {
         writeln!(result, "You started at {}", checkin_time.to_string()).unwrap();
@@ -369,19 +706,7 @@ fn create_main_screen(
             mandatory_break_time_for_working_time(preferred_working_time),
         );
 
-        if time_left.minutes >= 0 {
-            let finished_time = {
-                let current_time = time::get_current_datetime().to_timestamp();
-                let mut hours = current_time.hours;
-                let mut minutes = current_time.minutes + time_left.minutes as u32;
-                while minutes >= 60 {
-                    minutes -= 60;
-                    hours += 1;
-                }
-                hours = hours % 24;
-
-                TimeStamp::new(hours, minutes)
-            };
+        if let Some(finished_time) = get_finish_time(day_entry, preferred_working_time) {
             writeln!(
                 result,
                 "For a preferred work time of {} you will be finished at {} ({} left)",
@@ -393,9 +718,15 @@ fn create_main_screen(
         } else {
             writeln!(
                 result,
-                "You already finished your preferred work time of {} with overtime of {}",
+                "You already finished your preferred work time of {} with {}",
                 preferred_working_time.to_string(),
-                (TimeDuration::zero() - time_left).to_string()
+                theme.paint(
+                    theme.overtime,
+                    &format!(
+                        "overtime of {}",
+                        (TimeDuration::zero() - time_left).to_string()
+                    )
+                )
             )
             .unwrap();
         }
@@ -408,21 +739,31 @@ fn create_main_screen(
             result,
             "You are {} since {} [{}]",
             if current_activity.is_work {
-                format!("doing [{}]", &current_activity.name)
+                format!(
+                    "doing [{}]",
+                    theme.paint(
+                        theme.activity_style(&current_activity.name),
+                        &current_activity.name
+                    )
+                )
             } else {
-                "checked out".to_owned()
+                theme.paint(theme.dimmed, "checked out")
             },
             current_activity.time_start.to_string(),
-            current_activity.duration().to_string(),
+            current_activity.duration(day_entry.time_now()).to_string(),
         )
         .unwrap();
     } else {
-        writeln!(result, "").unwrap();
+        writeln!(result).unwrap();
     }
 
     writeln!(
         result,
-        "\n=================================================\n"
+        "\n{}\n",
+        theme.paint(
+            theme.separator,
+            "================================================="
+        )
     )
     .unwrap();
 
@@ -430,19 +771,60 @@ fn create_main_screen(
 
     writeln!(
         result,
-        "=================================================\n"
+        "{}\n",
+        theme.paint(
+            theme.separator,
+            "================================================="
+        )
     )
     .unwrap();
 
+    if config.show_timeline {
+        writeln!(
+            result,
+            "{}",
+            create_timeline_screen(
+                day_entry,
+                get_finish_time(day_entry, preferred_working_time),
+                theme,
+                terminal_width,
+            )
+        )
+        .unwrap();
+    }
+
+    clickable_rows.toggle_work = Some(current_row(&result));
     if day_entry.is_currently_working() {
-        writeln!(result, "(x) Take a break\n",).unwrap();
+        writeln!(
+            result,
+            "{} Take a break\n",
+            theme.paint(
+                theme.key_hint,
+                &format!("({})", keymap.key_hint(Action::ToggleWork))
+            )
+        )
+        .unwrap();
     } else {
-        writeln!(result, "<x> Begin work\n",).unwrap();
+        writeln!(
+            result,
+            "{}\n",
+            theme.paint(
+                theme.active,
+                &format!("<{}> Begin work", keymap.key_hint(Action::ToggleWork))
+            )
+        )
+        .unwrap();
     }
 
     let activity_durations = day_entry.get_activity_durations();
     let mut lines = Vec::new();
-    for (index, activity_name) in activity_names_list.iter().enumerate().take(9) {
+    for (index, activity) in activity_list
+        .iter()
+        .enumerate()
+        .skip(activity_list_scroll_offset)
+        .take(ACTIVITY_LIST_VISIBLE_COUNT)
+    {
+        let activity_name = &activity.name;
         let is_active = day_entry
             .get_current_activity()
             .map(|activity| activity.name == *activity_name)
@@ -451,47 +833,274 @@ fn create_main_screen(
             .get(activity_name)
             .unwrap_or(&TimeDuration::zero())
             .to_string();
-        lines.push((
-            duration,
-            if is_active {
-                format!("<{}> {} [{}]", index + 1, "Stop ", activity_name,)
-            } else {
-                format!("({}) {} [{}]", index + 1, "Begin", activity_name,)
-            },
-        ));
+        let key = keymap.key_hint(Action::ActivitySlot(index - activity_list_scroll_offset));
+        let prefix = if is_active {
+            format!("<{}> {}", key, "Stop ")
+        } else {
+            format!("({}) {}", key, "Begin")
+        };
+        let mut status = match activity.budget {
+            Some(budget) => {
+                let remaining = budget.duration.minutes
+                    - budget
+                        .tracked_duration(
+                            activity_name,
+                            &activity_durations,
+                            week_activity_durations_before,
+                        )
+                        .minutes;
+                if remaining < 0 {
+                    theme.paint(
+                        theme.overtime,
+                        &format!(
+                            " {} over budget",
+                            TimeDuration {
+                                minutes: -remaining
+                            }
+                            .to_string()
+                        ),
+                    )
+                } else {
+                    let period = match budget.period {
+                        Period::Day => "today",
+                        Period::Week => "this week",
+                    };
+                    theme.paint(
+                        theme.dimmed,
+                        &format!(
+                            " {} left {}",
+                            TimeDuration { minutes: remaining }.to_string(),
+                            period
+                        ),
+                    )
+                }
+            }
+            None => String::new(),
+        };
+        for goal in &activity.goals {
+            let tracked = goal.target.tracked_duration(
+                activity_name,
+                &activity_durations,
+                week_activity_durations_before,
+            );
+            let style = match goal.kind {
+                GoalKind::AtLeast if goal.is_reached(tracked) => theme.active,
+                GoalKind::AtMost if !goal.is_reached(tracked) => theme.overtime,
+                _ => theme.dimmed,
+            };
+            write!(
+                status,
+                " {}",
+                theme.paint(
+                    style,
+                    &format!(
+                        "{} {}",
+                        goal.progress_bar(tracked, GOAL_PROGRESS_BAR_WIDTH),
+                        goal.to_string()
+                    )
+                )
+            )
+            .unwrap();
+        }
+        lines.push((index, is_active, duration, status, prefix, activity_name));
     }
 
     let max_line_len = lines
         .iter()
-        .map(|(_activitiy_name, line)| line.len())
+        .map(
+            |(_index, _is_active, _duration, _status, prefix, activity_name)| {
+                prefix.chars().count() + activity_name.chars().count() + 3
+            },
+        )
         .max()
         .unwrap_or(0);
 
-    for (duration, line) in lines.into_iter() {
-        let padding = 2 + max_line_len - line.len();
-        write!(result, "{}", line).unwrap();
-        for _ in 0..padding {
-            write!(result, ".").unwrap();
-        }
-        if line.contains("Stop") {
-            writeln!(result, "[{}] <-- working", duration).unwrap();
+    for (index, is_active, duration, status, prefix, activity_name) in lines.into_iter() {
+        clickable_rows
+            .activities
+            .push((current_row(&result), index));
+        let line_len = prefix.chars().count() + activity_name.chars().count() + 3;
+        let padding = ".".repeat(2 + max_line_len - line_len);
+        let activity_name = theme.paint(theme.activity_style(activity_name), activity_name);
+        if is_active {
+            writeln!(
+                result,
+                "{}[{}]{}{}{}",
+                theme.paint(theme.active, &format!("{} ", prefix)),
+                activity_name,
+                theme.paint(theme.active, &format!("{}[{}]", padding, duration)),
+                status,
+                theme.paint(theme.active, " <-- working")
+            )
+            .unwrap();
         } else {
-            writeln!(result, "[{}]", duration).unwrap();
+            let style = if day_entry.is_currently_working() {
+                ContentStyle::new()
+            } else {
+                theme.dimmed
+            };
+            writeln!(
+                result,
+                "{}[{}]{}{}",
+                theme.paint(style, &format!("{} ", prefix)),
+                activity_name,
+                theme.paint(style, &format!("{}[{}]", padding, duration)),
+                status
+            )
+            .unwrap();
         }
     }
 
-    writeln!(result, "").unwrap();
-    writeln!(result, "(+/-) Increase/decrease preferred work time").unwrap();
+    if activity_list.len() > ACTIVITY_LIST_VISIBLE_COUNT {
+        writeln!(
+            result,
+            "{}",
+            theme.paint(
+                theme.dimmed,
+                &format!(
+                    "Showing activities {}-{} of {} (scroll with the mouse wheel)",
+                    activity_list_scroll_offset + 1,
+                    usize::min(
+                        activity_list_scroll_offset + ACTIVITY_LIST_VISIBLE_COUNT,
+                        activity_list.len()
+                    ),
+                    activity_list.len()
+                )
+            )
+        )
+        .unwrap();
+    }
+
+    writeln!(result).unwrap();
+    writeln!(
+        result,
+        "{} Assign non-specific work to activities",
+        theme.paint(
+            theme.key_hint,
+            &format!("({})", keymap.key_hint(Action::ToggleReclassify))
+        )
+    )
+    .unwrap();
+    writeln!(
+        result,
+        "{} Browse previous days  {} Calendar",
+        theme.paint(
+            theme.key_hint,
+            &format!("({})", keymap.key_hint(Action::PreviousDay))
+        ),
+        theme.paint(
+            theme.key_hint,
+            &format!("({})", keymap.key_hint(Action::ToggleCalendar))
+        )
+    )
+    .unwrap();
+    writeln!(
+        result,
+        "{} Increase/decrease preferred work time",
+        theme.paint(
+            theme.key_hint,
+            &format!(
+                "({}/{})",
+                keymap.key_hint(Action::IncreaseWorkTime),
+                keymap.key_hint(Action::DecreaseWorkTime)
+            )
+        )
+    )
+    .unwrap();
 
     write!(
         result,
-        "\nPlease select what you want to do by pressing numbers (1-9) or (x): ",
+        "\nPlease select what you want to do by pressing the keys above or by clicking ({} for help): ",
+        keymap.key_hint(Action::ToggleHelp)
     )
     .unwrap();
 
+    (result, clickable_rows)
+}
+
+/// Returns a box listing all key bindings which is drawn on top of the main screen
+fn create_help_screen(keymap: &Keymap, theme: &Theme) -> String {
+    let bindings = keymap.describe_bindings();
+    let keys_width = bindings
+        .iter()
+        .map(|(keys, _description)| keys.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut lines: Vec<String> = bindings
+        .iter()
+        .map(|(keys, description)| {
+            format!(
+                "{}{}  {}",
+                keys,
+                " ".repeat(keys_width - keys.chars().count()),
+                description
+            )
+        })
+        .collect();
+    lines.insert(
+        0,
+        format!("Key bindings (change them in '{}')", CONFIG_FILEPATH),
+    );
+    lines.insert(1, String::new());
+    lines.push(String::new());
+    lines.push("Press any key to close this help".to_owned());
+
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let border = format!("+{}+", "-".repeat(width + 2));
+
+    let mut result = String::new();
+    writeln!(result, "{}", theme.paint(theme.separator, &border)).unwrap();
+    for (index, line) in lines.iter().enumerate() {
+        let padding = " ".repeat(width - line.chars().count());
+        let line = if index == 0 {
+            theme.paint(theme.heading, line)
+        } else {
+            line.to_owned()
+        };
+        writeln!(
+            result,
+            "{} {}{} {}",
+            theme.paint(theme.separator, "|"),
+            line,
+            padding,
+            theme.paint(theme.separator, "|")
+        )
+        .unwrap();
+    }
+    write!(result, "{}", theme.paint(theme.separator, &border)).unwrap();
     result
 }
 
+/// Returns the time at which the preferred working time will be reached or `None` if it was
+/// already reached
+fn get_finish_time(
+    day_entry: &DayEntry,
+    preferred_working_time: TimeDuration,
+) -> Option<TimeStamp> {
+    let time_left = day_entry.get_time_left_for_the_day(
+        preferred_working_time,
+        mandatory_break_time_for_working_time(preferred_working_time),
+    );
+    if time_left.minutes < 0 {
+        return None;
+    }
+
+    let current_time = day_entry.time_now();
+    let mut hours = current_time.hours;
+    let mut minutes = current_time.minutes + time_left.minutes as u32;
+    while minutes >= 60 {
+        minutes -= 60;
+        hours += 1;
+    }
+    hours %= 24;
+
+    Some(TimeStamp::new(hours, minutes))
+}
+
 fn mandatory_break_time_for_working_time(working_time: TimeDuration) -> TimeDuration {
     if working_time.minutes - (9 * 60) > 0 {
         TimeDuration { minutes: 45 }
@@ -0,0 +1,136 @@
+use crate::activitylist::ACTIVITY_LIST_FILEPATH;
+use crate::config::CONFIG_FILEPATH;
+
+use thyme_core::storage::TODAY_TIMESHEET_FILEPATH;
+
+use ct_lib_core::path_exists;
+
+use std::path::{Path, PathBuf};
+
+/// Environment variable that selects the profile if no `--profile` argument is given
+pub const PROFILE_ENVIRONMENT_VARIABLE: &str = "THYME_PROFILE";
+
+/// Files and directories whose presence mark a directory as containing Thyme data
+fn is_data_directory(directory: &Path) -> bool {
+    [
+        ACTIVITY_LIST_FILEPATH,
+        CONFIG_FILEPATH,
+        "project_names.txt",
+        "database",
+        TODAY_TIMESHEET_FILEPATH,
+        "profiles",
+    ]
+    .iter()
+    .any(|filename| path_exists(&directory.join(filename).to_string_lossy()))
+}
+
+fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || "-_".contains(character))
+}
+
+/// Removes a `--profile NAME` or `--profile=NAME` argument from the given arguments and
+/// returns the profile name. Falls back to the `THYME_PROFILE` environment variable.
+pub fn take_profile_argument(args: &mut Vec<String>) -> Result<Option<String>, String> {
+    let mut result = None;
+    let mut index = 0;
+    while index < args.len() {
+        if args[index] == "--profile" {
+            if index + 1 >= args.len() {
+                return Err(format!("Missing profile name after '{}'", args[index]));
+            }
+            result = Some(args.remove(index + 1));
+            args.remove(index);
+        } else if let Some(name) = args[index].strip_prefix("--profile=") {
+            result = Some(name.to_owned());
+            args.remove(index);
+        } else {
+            index += 1;
+        }
+    }
+
+    let result = result.or_else(|| {
+        std::env::var(PROFILE_ENVIRONMENT_VARIABLE)
+            .ok()
+            .filter(|name| !name.is_empty())
+    });
+    if let Some(name) = &result {
+        if !is_valid_profile_name(name) {
+            return Err(format!(
+                "Invalid profile name '{}' - only letters, digits, '-' and '_' are allowed",
+                name
+            ));
+        }
+    }
+    Ok(result)
+}
+
+/// Returns the default location for Thyme data according to the XDG base directory
+/// specification (`$XDG_DATA_HOME/thyme` or `~/.local/share/thyme`). On Windows
+/// `%APPDATA%\thyme` is used instead.
+fn xdg_data_directory() -> Option<PathBuf> {
+    let from_environment = |name: &str| {
+        std::env::var_os(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+    if cfg!(windows) {
+        return from_environment("APPDATA").map(|directory| directory.join("thyme"));
+    }
+    from_environment("XDG_DATA_HOME")
+        .or_else(|| from_environment("HOME").map(|home| home.join(".local").join("share")))
+        .map(|directory| directory.join("thyme"))
+}
+
+/// Determines the directory where Thyme keeps its data:
+/// - The current directory if it already contains Thyme data or if Thyme was started from the
+///   directory of its executable (i.e. by double-clicking it)
+/// - Otherwise the XDG data directory
+///
+/// Every profile lives in its own subdirectory `profiles/<name>` of that directory.
+pub fn data_directory(profile: Option<&str>) -> PathBuf {
+    let current_directory = std::env::current_dir()
+        .unwrap_or_else(|error| panic!("Could not determine current directory - {}", error));
+    let executable_directory = std::env::current_exe()
+        .ok()
+        .and_then(|path| path.parent().map(|directory| directory.to_path_buf()));
+
+    let base_directory = if is_data_directory(&current_directory)
+        || executable_directory.as_ref() == Some(&current_directory)
+    {
+        current_directory
+    } else {
+        xdg_data_directory().unwrap_or(current_directory)
+    };
+
+    match profile {
+        Some(name) => base_directory.join("profiles").join(name),
+        None => base_directory,
+    }
+}
+
+/// Creates the data directory for the given profile if necessary and makes it the current
+/// directory so that all data files can be accessed via relative paths. Returns the previous
+/// current directory.
+pub fn enter_data_directory(profile: Option<&str>) -> PathBuf {
+    let launch_directory = std::env::current_dir()
+        .unwrap_or_else(|error| panic!("Could not determine current directory - {}", error));
+    let directory = data_directory(profile);
+    std::fs::create_dir_all(&directory).unwrap_or_else(|error| {
+        panic!(
+            "Could not create data directory '{}' - {}",
+            directory.display(),
+            error
+        )
+    });
+    std::env::set_current_dir(&directory).unwrap_or_else(|error| {
+        panic!(
+            "Could not change into data directory '{}' - {}",
+            directory.display(),
+            error
+        )
+    });
+    launch_directory
+}
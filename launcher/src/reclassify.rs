@@ -0,0 +1,305 @@
+use crate::config::Config;
+use crate::keymap::Action;
+use crate::ACTIVITY_LIST_VISIBLE_COUNT;
+
+use thyme_core::dayentry::ACTIVITY_NAME_NON_SPECIFIC_WORK;
+use thyme_core::{ActivityListEntry, DayEntry, TimeDuration, TimeStamp};
+
+use chrono::prelude::*;
+
+use std::fmt::Write;
+
+/// Screen for assigning the non-specific work intervals of the current week to activities.
+/// Today's entry is owned by the main loop and passed in, the previous days of the week are
+/// loaded from the database when the screen is opened.
+pub struct ReclassifyScreen {
+    previous_days: Vec<DayEntry>,
+    /// Index into the list of all non-specific intervals of the week
+    selected: usize,
+    /// If set, only the first part of the selected interval with this duration gets assigned
+    split_duration: Option<TimeDuration>,
+    /// Why the last assignment failed
+    error: Option<String>,
+}
+
+impl ReclassifyScreen {
+    pub fn new(today: &DayEntry) -> ReclassifyScreen {
+        let week_start =
+            today.date - chrono::Duration::days(today.date.weekday().num_days_from_monday() as i64);
+        let previous_days = if week_start < today.date {
+            DayEntry::load_range_from_database(
+                week_start,
+                today.date.pred(),
+                &today.clock,
+                &today.storage,
+            )
+            .unwrap_or_else(|error| panic!("{}", error))
+        } else {
+            Vec::new()
+        };
+
+        let mut result = ReclassifyScreen {
+            previous_days,
+            selected: 0,
+            split_duration: None,
+            error: None,
+        };
+        // Start with the first interval of today if there is one
+        result.selected = result
+            .intervals(today)
+            .iter()
+            .position(|(day_index, _activity_index)| *day_index == result.previous_days.len())
+            .unwrap_or(0);
+        result
+    }
+
+    /// Returns pairs of (day index, activity index) for all non-specific intervals where a day
+    /// index equal to the number of previous days refers to today
+    fn intervals(&self, today: &DayEntry) -> Vec<(usize, usize)> {
+        self.previous_days
+            .iter()
+            .chain(std::iter::once(today))
+            .enumerate()
+            .flat_map(|(day_index, day_entry)| {
+                day_entry
+                    .get_non_specific_work_indices()
+                    .into_iter()
+                    .map(move |activity_index| (day_index, activity_index))
+            })
+            .collect()
+    }
+
+    fn selected_interval(&self, today: &DayEntry) -> Option<(usize, usize)> {
+        let intervals = self.intervals(today);
+        intervals
+            .get(usize::min(self.selected, intervals.len().saturating_sub(1)))
+            .copied()
+    }
+
+    fn selected_duration(&self, today: &DayEntry) -> Option<TimeDuration> {
+        self.selected_interval(today)
+            .map(|(day_index, activity_index)| {
+                let day_entry = self.previous_days.get(day_index).unwrap_or(today);
+                day_entry.activities[activity_index].duration(day_entry.time_end_of_entry())
+            })
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+        self.split_duration = None;
+        self.error = None;
+    }
+
+    pub fn select_next(&mut self, today: &DayEntry) {
+        self.selected = usize::min(
+            self.selected + 1,
+            self.intervals(today).len().saturating_sub(1),
+        );
+        self.split_duration = None;
+        self.error = None;
+    }
+
+    /// Enters or leaves split mode. In split mode only the first part of the selected interval
+    /// gets assigned and the rest stays non-specific.
+    pub fn toggle_split(&mut self, today: &DayEntry) {
+        self.split_duration = match self.split_duration {
+            Some(_) => None,
+            None => self
+                .selected_duration(today)
+                .filter(|duration| duration.minutes >= 2)
+                .map(|duration| TimeDuration {
+                    minutes: duration.minutes / 2,
+                }),
+        };
+    }
+
+    /// Moves the split point of the selected interval by the given number of minutes
+    pub fn move_split(&mut self, today: &DayEntry, minutes: i32) {
+        if let (Some(split_duration), Some(duration)) =
+            (self.split_duration, self.selected_duration(today))
+        {
+            self.split_duration = Some(TimeDuration {
+                minutes: i32::max(
+                    1,
+                    i32::min(split_duration.minutes + minutes, duration.minutes - 1),
+                ),
+            });
+        }
+    }
+
+    /// Assigns the selected interval (or its first part in split mode) to the given activity
+    /// and writes back the changed timesheet
+    pub fn assign(
+        &mut self,
+        today: &mut DayEntry,
+        activity_name: &str,
+        config: &Config,
+        activity_list: &[ActivityListEntry],
+    ) {
+        let (day_index, activity_index) = match self.selected_interval(today) {
+            Some(interval) => interval,
+            None => return,
+        };
+        let previous_days_count = self.previous_days.len();
+        let day_entry = if day_index < previous_days_count {
+            &mut self.previous_days[day_index]
+        } else {
+            today
+        };
+
+        let parts = [(activity_name.to_owned(), self.split_duration)];
+        match day_entry.reassign_non_specific_work(activity_index, &parts) {
+            Ok(()) => {
+                day_entry
+                    .write_back()
+                    .and_then(|_| day_entry.write_report(&config.report, activity_list))
+                    .unwrap_or_else(|error| panic!("{}", error));
+                self.error = None;
+            }
+            Err(error) => self.error = Some(error),
+        }
+        self.split_duration = None;
+    }
+
+    pub fn render(
+        &self,
+        today: &DayEntry,
+        activity_list: &[ActivityListEntry],
+        activity_list_scroll_offset: usize,
+        config: &Config,
+    ) -> String {
+        let theme = &config.theme;
+        let keymap = &config.keymap;
+        let mut result = String::new();
+
+        writeln!(
+            result,
+            "{}",
+            theme.paint(
+                theme.heading,
+                "Assign non-specific work of this week to activities"
+            )
+        )
+        .unwrap();
+        writeln!(
+            result,
+            "\n{}\n",
+            theme.paint(
+                theme.separator,
+                "================================================="
+            )
+        )
+        .unwrap();
+
+        let intervals = self.intervals(today);
+        let selected_interval = self.selected_interval(today);
+        if intervals.is_empty() {
+            writeln!(result, "There is no non-specific work this week.").unwrap();
+        }
+        for (day_index, activity_index) in intervals.iter().copied() {
+            let day_entry = self.previous_days.get(day_index).unwrap_or(today);
+            let activity = &day_entry.activities[activity_index];
+            let time_end = activity
+                .time_end
+                .map(|time_end| time_end.to_string())
+                .unwrap_or_else(|| {
+                    if day_entry.is_today() {
+                        "<now>".to_owned()
+                    } else {
+                        day_entry.time_end_of_entry().to_string()
+                    }
+                });
+            let line = format!(
+                "{} {} - {} [{}]",
+                day_entry.date.format("%a %d.%m."),
+                activity.time_start.to_string(),
+                time_end,
+                activity.duration(day_entry.time_end_of_entry()).to_string()
+            );
+
+            if Some((day_index, activity_index)) == selected_interval {
+                writeln!(
+                    result,
+                    "{}",
+                    theme.paint(theme.active, &format!("> {}", line))
+                )
+                .unwrap();
+                if let Some(split_duration) = self.split_duration {
+                    let split_time = TimeStamp::from_minutes_of_day(
+                        activity.time_start.to_minutes_of_day() + split_duration.minutes,
+                    );
+                    writeln!(
+                        result,
+                        "    Split: assign {} - {} [{}], the rest stays {} {}",
+                        activity.time_start.to_string(),
+                        split_time.to_string(),
+                        split_duration.to_string(),
+                        ACTIVITY_NAME_NON_SPECIFIC_WORK,
+                        theme.paint(
+                            theme.key_hint,
+                            &format!(
+                                "({}/{} to move)",
+                                keymap.key_hint(Action::IncreaseWorkTime),
+                                keymap.key_hint(Action::DecreaseWorkTime)
+                            )
+                        )
+                    )
+                    .unwrap();
+                }
+            } else {
+                writeln!(result, "  {}", line).unwrap();
+            }
+        }
+
+        if let Some(error) = &self.error {
+            writeln!(
+                result,
+                "\n{}",
+                theme.paint(theme.overtime, &format!("Could not assign: {}", error))
+            )
+            .unwrap();
+        }
+
+        writeln!(result).unwrap();
+        for (index, activity) in activity_list
+            .iter()
+            .enumerate()
+            .skip(activity_list_scroll_offset)
+            .take(ACTIVITY_LIST_VISIBLE_COUNT)
+        {
+            let key = keymap.key_hint(Action::ActivitySlot(index - activity_list_scroll_offset));
+            writeln!(
+                result,
+                "{} Assign to [{}]",
+                theme.paint(theme.key_hint, &format!("({})", key)),
+                theme.paint(theme.activity_style(&activity.name), &activity.name)
+            )
+            .unwrap();
+        }
+
+        writeln!(result).unwrap();
+        writeln!(
+            result,
+            "{} Select interval  {} Split interval  {} Back",
+            theme.paint(
+                theme.key_hint,
+                &format!(
+                    "({}/{})",
+                    keymap.key_hint(Action::SelectPrevious),
+                    keymap.key_hint(Action::SelectNext)
+                )
+            ),
+            theme.paint(
+                theme.key_hint,
+                &format!("({})", keymap.key_hint(Action::SplitInterval))
+            ),
+            theme.paint(
+                theme.key_hint,
+                &format!("({})", keymap.key_hint(Action::ToggleReclassify))
+            ),
+        )
+        .unwrap();
+
+        result
+    }
+}
@@ -0,0 +1,201 @@
+use crossterm::{cursor, style::Print, QueueableCommand};
+
+use std::fmt::Write as FmtWrite;
+
+/// Graphics rendition of a single cell as understood by ANSI SGR escape sequences. Colors
+/// are stored as their raw SGR parameters (i.e. "31" or "38;2;255;0;0").
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct CellStyle {
+    foreground: Option<String>,
+    background: Option<String>,
+    attributes: Vec<u8>,
+}
+
+impl CellStyle {
+    /// Applies the parameters of an SGR escape sequence like `\x1b[1;38;5;10m`
+    fn apply_sgr_parameters(&mut self, parameters: &str) {
+        let parameters: Vec<&str> = parameters.split(';').collect();
+        let mut index = 0;
+        while index < parameters.len() {
+            let code: u8 = parameters[index].parse().unwrap_or(0);
+            match code {
+                0 => *self = CellStyle::default(),
+                1..=9 if !self.attributes.contains(&code) => {
+                    self.attributes.push(code);
+                    self.attributes.sort_unstable();
+                }
+                21..=29 => {
+                    // NOTE: 22 resets both bold and dim
+                    let reset = if code == 22 || code == 21 {
+                        vec![1, 2]
+                    } else {
+                        vec![code - 20]
+                    };
+                    self.attributes
+                        .retain(|attribute| !reset.contains(attribute));
+                }
+                30..=37 | 90..=97 => self.foreground = Some(code.to_string()),
+                40..=47 | 100..=107 => self.background = Some(code.to_string()),
+                39 => self.foreground = None,
+                49 => self.background = None,
+                38 | 48 => {
+                    // Extended colors are either `38;5;n` or `38;2;r;g;b`
+                    let length = match parameters.get(index + 1) {
+                        Some(&"5") => 3,
+                        Some(&"2") => 5,
+                        _ => 1,
+                    };
+                    let end = usize::min(index + length, parameters.len());
+                    let color = parameters[index..end].join(";");
+                    if code == 38 {
+                        self.foreground = Some(color);
+                    } else {
+                        self.background = Some(color);
+                    }
+                    index = end;
+                    continue;
+                }
+                _ => {}
+            }
+            index += 1;
+        }
+    }
+
+    fn to_escape_sequence(&self) -> String {
+        let mut result = "\x1b[0m".to_owned();
+        for attribute in &self.attributes {
+            write!(result, "\x1b[{}m", attribute).unwrap();
+        }
+        if let Some(foreground) = &self.foreground {
+            write!(result, "\x1b[{}m", foreground).unwrap();
+        }
+        if let Some(background) = &self.background {
+            write!(result, "\x1b[{}m", background).unwrap();
+        }
+        result
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cell {
+    character: char,
+    style: CellStyle,
+}
+
+/// An in-memory representation of the terminal contents. Text is drawn into the buffer first
+/// and only cells that changed compared to the previous frame are sent to the terminal.
+#[derive(Debug, Clone)]
+pub struct ScreenBuffer {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+    /// Where the cursor is placed after rendering
+    cursor: (usize, usize),
+}
+
+impl ScreenBuffer {
+    pub fn new(width: usize, height: usize) -> ScreenBuffer {
+        ScreenBuffer {
+            width,
+            height,
+            cells: vec![
+                Cell {
+                    character: ' ',
+                    style: CellStyle::default(),
+                };
+                width * height
+            ],
+            cursor: (0, 0),
+        }
+    }
+
+    /// Draws the given text with its top-left corner at the given position. The text may
+    /// contain newlines and ANSI SGR escape sequences for styling. Everything outside of the
+    /// buffer is clipped. The cursor is placed at the end of the text.
+    pub fn draw_text(&mut self, x: usize, y: usize, text: &str) {
+        let mut style = CellStyle::default();
+        let mut pos_x = x;
+        let mut pos_y = y;
+
+        let mut characters = text.chars();
+        while let Some(character) = characters.next() {
+            match character {
+                '\x1b' => {
+                    // Read escape sequence until its final byte
+                    let mut sequence = String::new();
+                    for next in characters.by_ref() {
+                        sequence.push(next);
+                        if next.is_ascii_alphabetic() || next == '~' {
+                            break;
+                        }
+                    }
+                    if let Some(parameters) = sequence
+                        .strip_prefix('[')
+                        .and_then(|sequence| sequence.strip_suffix('m'))
+                    {
+                        style.apply_sgr_parameters(parameters);
+                    }
+                }
+                '\n' => {
+                    pos_x = x;
+                    pos_y += 1;
+                }
+                '\r' => {}
+                _ => {
+                    if pos_x < self.width && pos_y < self.height {
+                        self.cells[pos_y * self.width + pos_x] = Cell {
+                            character,
+                            style: style.clone(),
+                        };
+                    }
+                    pos_x += 1;
+                }
+            }
+        }
+
+        self.cursor = (
+            usize::min(pos_x, self.width.saturating_sub(1)),
+            usize::min(pos_y, self.height.saturating_sub(1)),
+        );
+    }
+
+    /// Queues all cells that differ from the `previous` frame to the given output. If there
+    /// is no previous frame or it has a different size the whole buffer is queued.
+    pub fn queue_changes<W: std::io::Write>(
+        &self,
+        previous: Option<&ScreenBuffer>,
+        output: &mut W,
+    ) -> crossterm::Result<()> {
+        let previous = previous
+            .filter(|previous| previous.width == self.width && previous.height == self.height);
+
+        let mut current_style: Option<&CellStyle> = None;
+        for y in 0..self.height {
+            let mut next_x_without_move: Option<usize> = None;
+            for x in 0..self.width {
+                let cell = &self.cells[y * self.width + x];
+                if let Some(previous) = previous {
+                    if previous.cells[y * self.width + x] == *cell {
+                        continue;
+                    }
+                }
+
+                if next_x_without_move != Some(x) {
+                    output.queue(cursor::MoveTo(x as u16, y as u16))?;
+                }
+                if current_style != Some(&cell.style) {
+                    output.queue(Print(cell.style.to_escape_sequence()))?;
+                    current_style = Some(&cell.style);
+                }
+                output.queue(Print(cell.character))?;
+                next_x_without_move = Some(x + 1);
+            }
+        }
+
+        if current_style.is_some() {
+            output.queue(Print("\x1b[0m"))?;
+        }
+        output.queue(cursor::MoveTo(self.cursor.0 as u16, self.cursor.1 as u16))?;
+        Ok(())
+    }
+}
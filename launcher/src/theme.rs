@@ -0,0 +1,129 @@
+use thyme_core::report_html::activity_color_hue;
+
+use crossterm::style::{Color, ContentStyle, Stylize};
+
+#[derive(Debug, Copy, Clone)]
+pub struct Theme {
+    /// If true all styling is skipped and only plain text is emitted
+    pub is_monochrome: bool,
+    /// Lightness of the per-activity colors in 0..1
+    pub activity_lightness: f32,
+
+    pub heading: ContentStyle,
+    pub separator: ContentStyle,
+    pub active: ContentStyle,
+    pub overtime: ContentStyle,
+    pub dimmed: ContentStyle,
+    pub key_hint: ContentStyle,
+    pub timeline_empty: ContentStyle,
+    pub timeline_break: ContentStyle,
+}
+
+impl Theme {
+    /// Colors that work well on terminals with dark backgrounds
+    pub fn default() -> Theme {
+        Theme {
+            is_monochrome: false,
+            activity_lightness: 0.6,
+            heading: ContentStyle::new().bold(),
+            separator: ContentStyle::new().dark_grey(),
+            active: ContentStyle::new().green().bold(),
+            overtime: ContentStyle::new().red().bold(),
+            dimmed: ContentStyle::new().dark_grey(),
+            key_hint: ContentStyle::new().yellow(),
+            timeline_empty: ContentStyle::new().dark_grey(),
+            timeline_break: ContentStyle::new().grey(),
+        }
+    }
+
+    /// Colors that work well on terminals with light backgrounds
+    pub fn light() -> Theme {
+        Theme {
+            is_monochrome: false,
+            activity_lightness: 0.4,
+            heading: ContentStyle::new().bold(),
+            separator: ContentStyle::new().grey(),
+            active: ContentStyle::new().dark_green().bold(),
+            overtime: ContentStyle::new().dark_red().bold(),
+            dimmed: ContentStyle::new().grey(),
+            key_hint: ContentStyle::new().dark_blue(),
+            timeline_empty: ContentStyle::new().grey(),
+            timeline_break: ContentStyle::new().dark_grey(),
+        }
+    }
+
+    pub fn monochrome() -> Theme {
+        Theme {
+            is_monochrome: true,
+            activity_lightness: 0.0,
+            heading: ContentStyle::new(),
+            separator: ContentStyle::new(),
+            active: ContentStyle::new(),
+            overtime: ContentStyle::new(),
+            dimmed: ContentStyle::new(),
+            key_hint: ContentStyle::new(),
+            timeline_empty: ContentStyle::new(),
+            timeline_break: ContentStyle::new(),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Theme> {
+        match name {
+            "default" | "dark" => Some(Theme::default()),
+            "light" => Some(Theme::light()),
+            "monochrome" | "none" => Some(Theme::monochrome()),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the user asked for no colors via the `NO_COLOR` environment variable
+    /// (see https://no-color.org)
+    pub fn is_color_disabled_by_environment() -> bool {
+        std::env::var_os("NO_COLOR")
+            .map(|value| !value.is_empty())
+            .unwrap_or(false)
+    }
+
+    pub fn paint(&self, style: ContentStyle, text: &str) -> String {
+        if self.is_monochrome {
+            text.to_owned()
+        } else {
+            style.apply(text).to_string()
+        }
+    }
+
+    pub fn activity_color(&self, activity_name: &str) -> Color {
+        let (r, g, b) = hsl_to_rgb(
+            activity_color_hue(activity_name) as f32,
+            0.6,
+            self.activity_lightness,
+        );
+        Color::Rgb { r, g, b }
+    }
+
+    pub fn activity_style(&self, activity_name: &str) -> ContentStyle {
+        if self.is_monochrome {
+            ContentStyle::new()
+        } else {
+            ContentStyle::new().with(self.activity_color(activity_name))
+        }
+    }
+}
+
+/// Converts a color given in HSL (hue in degrees, saturation and lightness in 0..1) to RGB
+pub fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> (u8, u8, u8) {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let hue_section = (hue % 360.0) / 60.0;
+    let x = chroma * (1.0 - (hue_section % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue_section as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    let to_byte = |value: f32| (255.0 * (value + m)).round().clamp(0.0, 255.0) as u8;
+    (to_byte(r), to_byte(g), to_byte(b))
+}
@@ -0,0 +1,172 @@
+use crate::theme::Theme;
+
+use thyme_core::time::TimeStamp;
+use thyme_core::DayEntry;
+
+use std::fmt::Write;
+
+#[derive(Debug, Clone, PartialEq)]
+enum TimelineCell {
+    Empty,
+    Work(String),
+    Break,
+    Projected,
+}
+
+fn place_text(row: &mut [char], column: usize, text: &str) {
+    for (index, character) in text.chars().enumerate() {
+        if let Some(cell) = row.get_mut(column + index) {
+            *cell = character;
+        }
+    }
+}
+
+/// Places a '^' marker at the given column with the given text next to it. The text is put
+/// on the right side of the marker if there is enough space, otherwise on its left side. If
+/// the text would overlap the `occupied` column range only the marker itself is placed.
+/// Returns the column range covered by the marker and its text.
+fn place_marker(
+    row: &mut [char],
+    column: usize,
+    text: &str,
+    occupied: Option<(usize, usize)>,
+) -> (usize, usize) {
+    let overlaps = |range: (usize, usize)| {
+        occupied
+            .map(|occupied| range.0 <= occupied.1 && occupied.0 <= range.1)
+            .unwrap_or(false)
+    };
+
+    let label_length = text.chars().count() + 2;
+    let range_right = (column, column + label_length - 1);
+    if range_right.1 < row.len() && !overlaps(range_right) {
+        place_text(row, column, &format!("^ {}", text));
+        return range_right;
+    }
+    if column + 1 >= label_length {
+        let range_left = (column + 1 - label_length, column);
+        if !overlaps(range_left) {
+            place_text(row, range_left.0, &format!("{} ^", text));
+            return range_left;
+        }
+    }
+    if !overlaps((column, column)) {
+        place_text(row, column, "^");
+    }
+    (column, column)
+}
+
+/// Renders the given day as a horizontal bar with one colored segment per activity, followed
+/// by a marker line for the current time and the time when the preferred working time will
+/// be reached. Days in the past end where their ongoing activities end instead of at the current
+/// time.
+pub fn create_timeline_screen(
+    day_entry: &DayEntry,
+    finish_time: Option<TimeStamp>,
+    theme: &Theme,
+    terminal_width: usize,
+) -> String {
+    let is_today = day_entry.is_today();
+    let now_timestamp = day_entry.time_end_of_entry();
+    let now = now_timestamp.to_minutes_of_day();
+    let first_checkin = day_entry
+        .first_checkin_time()
+        .map(|timestamp| timestamp.to_minutes_of_day())
+        .unwrap_or(now);
+    // NOTE: A finish time before now means that it wrapped around midnight
+    let finish = finish_time
+        .map(|timestamp| timestamp.to_minutes_of_day())
+        .filter(|finish| *finish >= now);
+
+    let range_start = 60 * (i32::min(first_checkin, now) / 60);
+    let range_end = 60 * ((i32::max(now, finish.unwrap_or(now)) + 59) / 60);
+    let range_end = i32::min(24 * 60, i32::max(range_end, range_start + 8 * 60));
+    let range_start = i32::min(range_start, range_end - 8 * 60);
+    let range_length = range_end - range_start;
+
+    let width = usize::max(terminal_width, 10);
+    let column_for_minute = |minute: i32| -> usize {
+        let column = (minute - range_start) as i64 * width as i64 / range_length as i64;
+        usize::min(column.max(0) as usize, width - 1)
+    };
+
+    let cells: Vec<TimelineCell> = (0..width)
+        .map(|column| {
+            let minute =
+                range_start + ((2 * column + 1) * range_length as usize / (2 * width)) as i32;
+            let activity = day_entry.activities.iter().find(|activity| {
+                let start = activity.time_start.to_minutes_of_day();
+                start <= minute && minute < start + activity.duration(now_timestamp).minutes
+            });
+            match activity {
+                Some(activity) if activity.is_work => TimelineCell::Work(activity.name.clone()),
+                Some(_) => TimelineCell::Break,
+                None => match finish {
+                    Some(finish) if now <= minute && minute < finish => TimelineCell::Projected,
+                    _ => TimelineCell::Empty,
+                },
+            }
+        })
+        .collect();
+
+    let mut result = String::new();
+
+    // Hour labels
+    let mut axis = vec![' '; width];
+    let label_width = 6;
+    let hours_per_label = (1..=24)
+        .find(|hours| width * *hours * 60 / range_length as usize >= label_width)
+        .unwrap_or(24);
+    for hour in (range_start / 60..=range_end / 60).step_by(hours_per_label) {
+        let column = column_for_minute(hour * 60);
+        if column + label_width - 1 <= width {
+            place_text(&mut axis, column, &format!("{:02}:00", hour));
+        }
+    }
+    writeln!(result, "{}", axis.iter().collect::<String>()).unwrap();
+
+    // Bar with one colored run per activity
+    let mut column = 0;
+    while column < cells.len() {
+        let cell = &cells[column];
+        let run_length = cells[column..]
+            .iter()
+            .take_while(|other| *other == cell)
+            .count();
+        let (character, style) = match cell {
+            TimelineCell::Empty => ("·", theme.timeline_empty),
+            TimelineCell::Work(name) => ("█", theme.activity_style(name)),
+            TimelineCell::Break => ("░", theme.timeline_break),
+            TimelineCell::Projected => ("─", theme.timeline_empty),
+        };
+        write!(
+            result,
+            "{}",
+            theme.paint(style, &character.repeat(run_length))
+        )
+        .unwrap();
+        column += run_length;
+    }
+    writeln!(result).unwrap();
+
+    // Markers for now and the projected finish time
+    let mut markers = vec![' '; width];
+    let now_text = if is_today {
+        format!("now {}", now_timestamp.to_string())
+    } else {
+        format!("end {}", now_timestamp.to_string())
+    };
+    let now_range = place_marker(&mut markers, column_for_minute(now), &now_text, None);
+    if let (Some(finish), Some(finish_time)) = (finish, finish_time) {
+        let finish_text = format!("finish {}", finish_time.to_string());
+        place_marker(
+            &mut markers,
+            column_for_minute(finish),
+            &finish_text,
+            Some(now_range),
+        );
+    }
+    writeln!(result, "{}", markers.iter().collect::<String>()).unwrap();
+
+    result
+}
@@ -0,0 +1,148 @@
+use ct_lib_core::{path_exists, path_last_modified_time};
+
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+
+use std::{
+    path::Path,
+    sync::mpsc::{channel, Receiver},
+    time::Duration,
+};
+
+/// Editors often save files by writing to a temporary file and renaming it to the original
+/// name afterwards. We wait for such bursts of events to settle before reporting a change.
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct FileChanges {
+    pub timesheet: bool,
+    pub activity_list: bool,
+    pub config: bool,
+}
+
+impl FileChanges {
+    fn all() -> FileChanges {
+        FileChanges {
+            timesheet: true,
+            activity_list: true,
+            config: true,
+        }
+    }
+}
+
+enum WatcherBackend {
+    /// Uses the native file system notifications of the OS (i.e. inotify on Linux)
+    Native {
+        _watcher: RecommendedWatcher,
+        receiver: Receiver<DebouncedEvent>,
+    },
+    /// Compares the last modified times of the watched files on every poll
+    Polling { last_modified_times: Vec<f64> },
+}
+
+/// Watches the timesheet, activity list and config files of the current directory for
+/// changes. Falls back to polling modification times if the OS notifications are unavailable.
+pub struct FileWatcher {
+    timesheet_filepath: String,
+    activity_list_filepath: String,
+    config_filepath: String,
+    backend: WatcherBackend,
+}
+
+impl FileWatcher {
+    pub fn new(
+        timesheet_filepath: &str,
+        activity_list_filepath: &str,
+        config_filepath: &str,
+    ) -> FileWatcher {
+        let backend = {
+            let (sender, receiver) = channel();
+            // NOTE: We watch the whole directory instead of the files themselves so that we
+            //       still get notified after a file was replaced via rename
+            match notify::watcher(sender, DEBOUNCE_DELAY).and_then(|mut watcher| {
+                watcher
+                    .watch(".", RecursiveMode::NonRecursive)
+                    .map(|_| watcher)
+            }) {
+                Ok(watcher) => WatcherBackend::Native {
+                    _watcher: watcher,
+                    receiver,
+                },
+                Err(_) => WatcherBackend::Polling {
+                    last_modified_times: Vec::new(),
+                },
+            }
+        };
+
+        FileWatcher {
+            timesheet_filepath: timesheet_filepath.to_owned(),
+            activity_list_filepath: activity_list_filepath.to_owned(),
+            config_filepath: config_filepath.to_owned(),
+            backend,
+        }
+    }
+
+    /// Returns which of the watched files changed since the last call
+    pub fn poll_changes(&mut self) -> FileChanges {
+        match &mut self.backend {
+            WatcherBackend::Native { receiver, .. } => {
+                let mut result = FileChanges::default();
+                for event in receiver.try_iter() {
+                    match event {
+                        DebouncedEvent::Create(path)
+                        | DebouncedEvent::Write(path)
+                        | DebouncedEvent::Remove(path)
+                        | DebouncedEvent::Rename(_, path) => {
+                            let filename = path
+                                .file_name()
+                                .map(|filename| filename.to_string_lossy().to_string())
+                                .unwrap_or_default();
+                            let matches = |filepath: &str| {
+                                Path::new(filepath)
+                                    .file_name()
+                                    .map(|name| name.to_string_lossy() == filename)
+                                    .unwrap_or(false)
+                            };
+                            result.timesheet |= matches(&self.timesheet_filepath);
+                            result.activity_list |= matches(&self.activity_list_filepath);
+                            result.config |= matches(&self.config_filepath);
+                        }
+                        DebouncedEvent::Rescan => result = FileChanges::all(),
+                        _ => {}
+                    }
+                }
+                result
+            }
+            WatcherBackend::Polling {
+                last_modified_times,
+            } => {
+                let modified_times: Vec<f64> = [
+                    &self.timesheet_filepath,
+                    &self.activity_list_filepath,
+                    &self.config_filepath,
+                ]
+                .iter()
+                .map(|filepath| {
+                    if path_exists(filepath) {
+                        path_last_modified_time(filepath)
+                    } else {
+                        0.0
+                    }
+                })
+                .collect();
+                let has_changed = |index: usize| {
+                    last_modified_times
+                        .get(index)
+                        .map(|last| *last != modified_times[index])
+                        .unwrap_or(true)
+                };
+                let result = FileChanges {
+                    timesheet: has_changed(0),
+                    activity_list: has_changed(1),
+                    config: has_changed(2),
+                };
+                *last_modified_times = modified_times;
+                result
+            }
+        }
+    }
+}
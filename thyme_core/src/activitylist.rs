@@ -0,0 +1,106 @@
+use crate::budget::PeriodDuration;
+use crate::goals::{Goal, GoalKind};
+
+/// An activity as listed in the activity list file. Every line consists of the activity name
+/// optionally followed by attributes separated by '|' like so:
+/// `Bugfixing | client: ACME Corp | rate: 85 | budget: 02:00/day | max: 05:00/week | tags: dev, qa`
+#[derive(Debug, Clone)]
+pub struct ActivityListEntry {
+    pub name: String,
+    pub client: Option<String>,
+    pub hourly_rate: Option<f64>,
+    /// Planned time for this activity per day or week
+    pub budget: Option<PeriodDuration>,
+    /// Minimum and maximum durations per day or week for this activity
+    pub goals: Vec<Goal>,
+    /// Free-form labels to group activities in queries
+    pub tags: Vec<String>,
+}
+
+impl ActivityListEntry {
+    pub fn from_string(input: &str) -> Result<ActivityListEntry, String> {
+        let mut parts = input.split('|').map(|part| part.trim());
+        let name = parts.next().unwrap_or("").to_owned();
+        if name.len() > 70 {
+            return Err(format!(
+                "Activity name [{}] is too long - please make it shorter than 70 character",
+                name
+            ));
+        }
+
+        let mut result = ActivityListEntry {
+            name,
+            client: None,
+            hourly_rate: None,
+            budget: None,
+            goals: Vec::new(),
+            tags: Vec::new(),
+        };
+        for attribute in parts.filter(|part| !part.is_empty()) {
+            let (key, value) = attribute.split_once(':').ok_or_else(|| {
+                format!(
+                    "Attribute '{}' of activity [{}] is not of the form 'key: value'",
+                    attribute, result.name
+                )
+            })?;
+            let key = key.trim();
+            let value = value.trim();
+            match key {
+                "client" => result.client = Some(value.to_owned()),
+                "rate" => {
+                    result.hourly_rate = Some(value.parse().map_err(|error| {
+                        format!(
+                            "Hourly rate '{}' of activity [{}] is not a valid number: {}",
+                            value, result.name, error
+                        )
+                    })?)
+                }
+                "budget" => {
+                    result.budget = Some(PeriodDuration::from_string(value).ok_or_else(|| {
+                        format!(
+                            "Budget '{}' of activity [{}] is not of the form 'HH:MM/day' or 'HH:MM/week'",
+                            value, result.name
+                        )
+                    })?)
+                }
+                "min" | "max" => {
+                    let kind = if key == "min" {
+                        GoalKind::AtLeast
+                    } else {
+                        GoalKind::AtMost
+                    };
+                    let target = PeriodDuration::from_string(value).ok_or_else(|| {
+                        format!(
+                            "Goal '{}' of activity [{}] is not of the form 'HH:MM/day' or 'HH:MM/week'",
+                            value, result.name
+                        )
+                    })?;
+                    result.goals.push(Goal { kind, target });
+                }
+                "tags" => result.tags.extend(
+                    value
+                        .split(',')
+                        .map(|tag| tag.trim())
+                        .filter(|tag| !tag.is_empty())
+                        .map(|tag| tag.to_owned()),
+                ),
+                _ => {
+                    return Err(format!(
+                        "Unknown attribute '{}' of activity [{}] - known attributes are 'client', 'rate', 'budget', 'min', 'max' and 'tags'",
+                        key, result.name
+                    ))
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Parses an activity list with one activity per line. Empty lines are ignored.
+pub fn parse_activity_list(input: &str) -> Result<Vec<ActivityListEntry>, String> {
+    input
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(ActivityListEntry::from_string)
+        .collect()
+}
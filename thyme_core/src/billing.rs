@@ -0,0 +1,302 @@
+use crate::activitylist::ActivityListEntry;
+use crate::dayentry::DayEntry;
+use crate::report_html::escape_html;
+use crate::rounding::RoundingPolicy;
+use crate::time::TimeDuration;
+
+use chrono::prelude::*;
+
+use std::fmt::Write;
+
+const DATE_FORMAT_INVOICE: &str = "%d.%m.%Y";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InvoiceFormat {
+    Text,
+    Markdown,
+    Html,
+}
+
+impl InvoiceFormat {
+    pub fn from_string(input: &str) -> Option<InvoiceFormat> {
+        match input {
+            "text" | "txt" => Some(InvoiceFormat::Text),
+            "markdown" | "md" => Some(InvoiceFormat::Markdown),
+            "html" => Some(InvoiceFormat::Html),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InvoiceLineItem {
+    pub activity_name: String,
+    pub client: Option<String>,
+    pub hourly_rate: f64,
+    pub duration_tracked: TimeDuration,
+    pub duration_billed: TimeDuration,
+}
+
+impl InvoiceLineItem {
+    pub fn amount(&self) -> f64 {
+        self.hourly_rate * self.duration_billed.minutes as f64 / 60.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Invoice {
+    pub date_from: NaiveDate,
+    pub date_to: NaiveDate,
+    pub client: Option<String>,
+    pub line_items: Vec<InvoiceLineItem>,
+}
+
+impl Invoice {
+    /// Creates line items for all activities of the activity list that have an hourly rate
+    /// (and match the given client if any). The billed durations are rounded according to the
+    /// given rounding policy.
+    pub fn create(
+        day_entries: &[DayEntry],
+        activity_list: &[ActivityListEntry],
+        client: Option<&str>,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        rounding: &RoundingPolicy,
+    ) -> Invoice {
+        let mut line_items = Vec::new();
+        for billable_activity in activity_list {
+            let hourly_rate = match billable_activity.hourly_rate {
+                Some(hourly_rate) => hourly_rate,
+                None => continue,
+            };
+            if client.is_some() && billable_activity.client.as_deref() != client {
+                continue;
+            }
+
+            let mut duration_tracked = TimeDuration::zero();
+            let mut duration_billed = TimeDuration::zero();
+            for day_entry in day_entries {
+                if let Some(duration) = day_entry
+                    .get_activity_durations()
+                    .get(&billable_activity.name)
+                {
+                    duration_tracked += *duration;
+                }
+                if let Some(duration) = rounding
+                    .get_activity_durations_rounded(day_entry)
+                    .get(&billable_activity.name)
+                {
+                    duration_billed += *duration;
+                }
+            }
+
+            if duration_tracked.minutes == 0 {
+                continue;
+            }
+            line_items.push(InvoiceLineItem {
+                activity_name: billable_activity.name.clone(),
+                client: billable_activity.client.clone(),
+                hourly_rate,
+                duration_tracked,
+                duration_billed,
+            });
+        }
+
+        Invoice {
+            date_from,
+            date_to,
+            client: client.map(|client| client.to_owned()),
+            line_items,
+        }
+    }
+
+    pub fn get_duration_tracked_total(&self) -> TimeDuration {
+        self.line_items
+            .iter()
+            .fold(TimeDuration::zero(), |acc, item| {
+                acc + item.duration_tracked
+            })
+    }
+
+    pub fn get_duration_billed_total(&self) -> TimeDuration {
+        self.line_items
+            .iter()
+            .fold(TimeDuration::zero(), |acc, item| acc + item.duration_billed)
+    }
+
+    pub fn get_amount_total(&self) -> f64 {
+        self.line_items.iter().map(|item| item.amount()).sum()
+    }
+
+    pub fn to_string(&self, format: InvoiceFormat) -> String {
+        match format {
+            InvoiceFormat::Text => self.write_text(),
+            InvoiceFormat::Markdown => self.write_markdown(),
+            InvoiceFormat::Html => self.write_html(),
+        }
+    }
+
+    fn title(&self) -> String {
+        if let Some(client) = &self.client {
+            format!("Invoice for {}", client)
+        } else {
+            "Invoice".to_owned()
+        }
+    }
+
+    fn period(&self) -> String {
+        format!(
+            "Period: {} - {}",
+            self.date_from.format(DATE_FORMAT_INVOICE),
+            self.date_to.format(DATE_FORMAT_INVOICE)
+        )
+    }
+
+    fn line_item_label(&self, item: &InvoiceLineItem) -> String {
+        match (&self.client, &item.client) {
+            (None, Some(client)) => format!("{} ({})", item.activity_name, client),
+            _ => item.activity_name.clone(),
+        }
+    }
+
+    fn write_text(&self) -> String {
+        let mut result = String::new();
+
+        writeln!(result, "{}", self.title()).unwrap();
+        writeln!(result, "{}\n", self.period()).unwrap();
+
+        let label_width = self
+            .line_items
+            .iter()
+            .map(|item| self.line_item_label(item).len())
+            .max()
+            .unwrap_or(0)
+            .max("Activity".len());
+        let separator = "-".repeat(label_width + 42);
+
+        writeln!(
+            result,
+            "{: <width$}  {: >9}  {: >8}  {: >8}  {: >10}",
+            "Activity",
+            "Rate",
+            "Tracked",
+            "Billed",
+            "Amount",
+            width = label_width
+        )
+        .unwrap();
+        writeln!(result, "{}", separator).unwrap();
+        for item in &self.line_items {
+            writeln!(
+                result,
+                "{: <width$}  {: >9.2}  {: >8}  {: >8}  {: >10.2}",
+                self.line_item_label(item),
+                item.hourly_rate,
+                item.duration_tracked.to_string(),
+                item.duration_billed.to_string(),
+                item.amount(),
+                width = label_width
+            )
+            .unwrap();
+        }
+        writeln!(result, "{}", separator).unwrap();
+        writeln!(
+            result,
+            "{: <width$}  {: >9}  {: >8}  {: >8}  {: >10.2}",
+            "Total",
+            "",
+            self.get_duration_tracked_total().to_string(),
+            self.get_duration_billed_total().to_string(),
+            self.get_amount_total(),
+            width = label_width
+        )
+        .unwrap();
+
+        result
+    }
+
+    fn write_markdown(&self) -> String {
+        let mut result = String::new();
+
+        writeln!(result, "# {}\n", self.title()).unwrap();
+        writeln!(result, "{}\n", self.period()).unwrap();
+
+        writeln!(result, "| Activity | Rate | Tracked | Billed | Amount |").unwrap();
+        writeln!(result, "|---|---:|---:|---:|---:|").unwrap();
+        for item in &self.line_items {
+            writeln!(
+                result,
+                "| {} | {:.2} | {} | {} | {:.2} |",
+                self.line_item_label(item).replace('|', "\\|"),
+                item.hourly_rate,
+                item.duration_tracked.to_string(),
+                item.duration_billed.to_string(),
+                item.amount(),
+            )
+            .unwrap();
+        }
+        writeln!(
+            result,
+            "| **Total** | | **{}** | **{}** | **{:.2}** |",
+            self.get_duration_tracked_total().to_string(),
+            self.get_duration_billed_total().to_string(),
+            self.get_amount_total(),
+        )
+        .unwrap();
+
+        result
+    }
+
+    fn write_html(&self) -> String {
+        let mut result = String::new();
+
+        writeln!(result, "<!DOCTYPE html>").unwrap();
+        writeln!(result, "<html>\n<head>\n<meta charset=\"utf-8\">").unwrap();
+        writeln!(result, "<title>{}</title>", escape_html(&self.title())).unwrap();
+        writeln!(
+            result,
+            "<style>
+body {{ font-family: sans-serif; }}
+table {{ border-collapse: collapse; }}
+th, td {{ padding: 4px 12px; border-bottom: 1px solid #ccc; }}
+td.number, th.number {{ text-align: right; }}
+tr.total td {{ font-weight: bold; border-top: 2px solid #000; }}
+</style>"
+        )
+        .unwrap();
+        writeln!(result, "</head>\n<body>").unwrap();
+        writeln!(result, "<h1>{}</h1>", escape_html(&self.title())).unwrap();
+        writeln!(result, "<p>{}</p>", escape_html(&self.period())).unwrap();
+
+        writeln!(result, "<table>").unwrap();
+        writeln!(
+            result,
+            "<tr><th>Activity</th><th class=\"number\">Rate</th><th class=\"number\">Tracked</th><th class=\"number\">Billed</th><th class=\"number\">Amount</th></tr>"
+        )
+        .unwrap();
+        for item in &self.line_items {
+            writeln!(
+                result,
+                "<tr><td>{}</td><td class=\"number\">{:.2}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{:.2}</td></tr>",
+                escape_html(&self.line_item_label(item)),
+                item.hourly_rate,
+                item.duration_tracked.to_string(),
+                item.duration_billed.to_string(),
+                item.amount(),
+            )
+            .unwrap();
+        }
+        writeln!(
+            result,
+            "<tr class=\"total\"><td>Total</td><td></td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{:.2}</td></tr>",
+            self.get_duration_tracked_total().to_string(),
+            self.get_duration_billed_total().to_string(),
+            self.get_amount_total(),
+        )
+        .unwrap();
+        writeln!(result, "</table>").unwrap();
+        writeln!(result, "</body>\n</html>").unwrap();
+
+        result
+    }
+}
@@ -0,0 +1,229 @@
+use crate::activitylist::ActivityListEntry;
+use crate::clock::SharedClock;
+use crate::dayentry::DayEntry;
+use crate::storage::{SharedStorage, Timesheet};
+use crate::time::TimeDuration;
+
+use ct_lib_core::indexmap::IndexMap;
+
+use chrono::prelude::*;
+
+use std::fmt::Write;
+use std::sync::Mutex;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Period {
+    Day,
+    Week,
+}
+
+impl Period {
+    pub fn to_string(self) -> &'static str {
+        match self {
+            Period::Day => "day",
+            Period::Week => "week",
+        }
+    }
+}
+
+/// A duration per day or per week as given in the activity list like `02:00/day` or
+/// `05:00/week`. Durations without period are per day.
+#[derive(Debug, Copy, Clone)]
+pub struct PeriodDuration {
+    pub duration: TimeDuration,
+    pub period: Period,
+}
+
+impl PeriodDuration {
+    pub fn from_string(input: &str) -> Option<PeriodDuration> {
+        let (duration, period) = match input.split_once('/') {
+            Some((duration, period)) => (duration, period.trim()),
+            None => (input, "day"),
+        };
+        let period = match period {
+            "day" | "d" => Period::Day,
+            "week" | "w" => Period::Week,
+            _ => return None,
+        };
+        Some(PeriodDuration {
+            duration: TimeDuration::from_string(duration)?,
+            period,
+        })
+    }
+
+    pub fn to_string(self) -> String {
+        format!("{}/{}", self.duration.to_string(), self.period.to_string())
+    }
+
+    /// Returns the tracked duration of the given activity that counts towards this duration.
+    /// For weekly durations `week_durations_before` must contain the activity durations of the
+    /// previous days of the week.
+    pub fn tracked_duration(
+        &self,
+        activity_name: &str,
+        day_durations: &IndexMap<String, TimeDuration>,
+        week_durations_before: &IndexMap<String, TimeDuration>,
+    ) -> TimeDuration {
+        let day_duration = day_durations
+            .get(activity_name)
+            .copied()
+            .unwrap_or(TimeDuration::zero());
+        match self.period {
+            Period::Day => day_duration,
+            Period::Week => TimeDuration {
+                minutes: day_duration.minutes
+                    + week_durations_before
+                        .get(activity_name)
+                        .map(|duration| duration.minutes)
+                        .unwrap_or(0),
+            },
+        }
+    }
+}
+
+/// Sums up the activity durations of all days of the week of the given date that lie before it
+pub fn load_week_activity_durations_before(
+    date: NaiveDate,
+    clock: &SharedClock,
+    storage: &SharedStorage,
+) -> Result<IndexMap<String, TimeDuration>, String> {
+    let week_start = date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+    let mut result: IndexMap<String, TimeDuration> = IndexMap::new();
+    if week_start == date {
+        return Ok(result);
+    }
+    for day_entry in DayEntry::load_range_from_database(week_start, date.pred(), clock, storage)? {
+        for (activity_name, duration) in day_entry.get_activity_durations() {
+            result
+                .entry(activity_name)
+                .or_insert(TimeDuration::zero())
+                .minutes += duration.minutes;
+        }
+    }
+    Ok(result)
+}
+
+/// Remembers the activity durations of the days of a week before a date so that they are only
+/// loaded again if one of their timesheets changed
+#[derive(Default)]
+pub struct WeekDurationsCache {
+    cached: Mutex<Option<CachedWeekDurations>>,
+}
+
+struct CachedWeekDurations {
+    date: NaiveDate,
+    modified_times: Vec<Option<f64>>,
+    durations: IndexMap<String, TimeDuration>,
+}
+
+impl WeekDurationsCache {
+    /// Same as `load_week_activity_durations_before` but reuses the last result for the same
+    /// date if no timesheet of the week changed since
+    pub fn load_before(
+        &self,
+        date: NaiveDate,
+        clock: &SharedClock,
+        storage: &SharedStorage,
+    ) -> Result<IndexMap<String, TimeDuration>, String> {
+        let week_start =
+            date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+        let modified_times: Vec<Option<f64>> = week_start
+            .iter_days()
+            .take_while(|day| *day < date)
+            .map(|day| storage.timesheet_modified_time(Timesheet::Day(day)))
+            .collect();
+
+        let mut cached = self.cached.lock().unwrap();
+        if let Some(cached) = cached.as_ref() {
+            if cached.date == date && cached.modified_times == modified_times {
+                return Ok(cached.durations.clone());
+            }
+        }
+        let durations = load_week_activity_durations_before(date, clock, storage)?;
+        *cached = Some(CachedWeekDurations {
+            date,
+            modified_times,
+            durations: durations.clone(),
+        });
+        Ok(durations)
+    }
+}
+
+/// A row of the plan-vs-actual comparison
+pub struct PlanComparison {
+    pub activity_name: String,
+    pub budget: PeriodDuration,
+    pub tracked: TimeDuration,
+}
+
+impl PlanComparison {
+    pub fn remaining(&self) -> TimeDuration {
+        TimeDuration {
+            minutes: self.budget.duration.minutes - self.tracked.minutes,
+        }
+    }
+
+    pub fn is_over_budget(&self) -> bool {
+        self.remaining().minutes < 0
+    }
+}
+
+/// Compares the planned budgets of the activity list with the tracked durations of the given day
+/// (and its week for weekly budgets)
+pub fn compare_plan_with_actual(
+    day_entry: &DayEntry,
+    activity_list: &[ActivityListEntry],
+) -> Result<Vec<PlanComparison>, String> {
+    if activity_list
+        .iter()
+        .all(|activity| activity.budget.is_none())
+    {
+        return Ok(Vec::new());
+    }
+
+    let day_durations = day_entry.get_activity_durations();
+    let week_durations_before = day_entry.load_week_durations_before()?;
+    Ok(activity_list
+        .iter()
+        .filter_map(|activity| {
+            activity.budget.map(|budget| PlanComparison {
+                activity_name: activity.name.clone(),
+                budget,
+                tracked: budget.tracked_duration(
+                    &activity.name,
+                    &day_durations,
+                    &week_durations_before,
+                ),
+            })
+        })
+        .collect())
+}
+
+pub fn write_plan_vs_actual(comparisons: &[PlanComparison]) -> String {
+    let mut result = String::new();
+    if comparisons.is_empty() {
+        return result;
+    }
+
+    writeln!(result, "\nPlan vs. Actual:").unwrap();
+    writeln!(result, "=====================\n").unwrap();
+    writeln!(result, "Budget         Actual   Remaining  Activity").unwrap();
+    for comparison in comparisons {
+        writeln!(
+            result,
+            "{: <14} {: <8} {: <10} {}{}",
+            comparison.budget.to_string(),
+            comparison.tracked.to_string(),
+            comparison.remaining().to_string(),
+            comparison.activity_name,
+            if comparison.is_over_budget() {
+                "  <-- OVER BUDGET"
+            } else {
+                ""
+            }
+        )
+        .unwrap();
+    }
+    writeln!(result).unwrap();
+    result
+}
@@ -0,0 +1,567 @@
+use crate::activitylist::ActivityListEntry;
+use crate::dayentry::{
+    DayEntry, StampEvent, ACTIVITY_NAME_NON_SPECIFIC_WORK, DATE_FORMAT_TIMESHEET, TIME_END_OF_DAY,
+};
+use crate::storage::{Storage, Timesheet};
+
+use chrono::prelude::*;
+
+use std::fmt::Write;
+
+/// A problem found in a timesheet of the database
+#[derive(Debug, Clone)]
+pub struct Problem {
+    /// Human readable location of the timesheet, i.e. its file path
+    pub location: String,
+    /// Line in the timesheet starting at 1 or `None` if the problem concerns the whole timesheet
+    pub line: Option<usize>,
+    pub message: String,
+    /// Description of the automatic fix or `None` if the problem needs to be fixed by hand
+    pub fix: Option<String>,
+    /// True if the automatic fix was written back to the storage
+    pub is_fixed: bool,
+}
+
+impl Problem {
+    fn new(location: &str, line: Option<usize>, message: String) -> Problem {
+        Problem {
+            location: location.to_owned(),
+            line,
+            message,
+            fix: None,
+            is_fixed: false,
+        }
+    }
+
+    fn with_fix(mut self, fix: String) -> Problem {
+        self.fix = Some(fix);
+        self
+    }
+
+    /// Formats the problem as `location:line: message` followed by the possible fix
+    pub fn to_string(&self) -> String {
+        let mut result = self.location.clone();
+        if let Some(line) = self.line {
+            write!(result, ":{}", line).unwrap();
+        }
+        write!(result, ": {}", self.message).unwrap();
+        match (&self.fix, self.is_fixed) {
+            (Some(fix), true) => write!(result, " (fixed: {})", fix).unwrap(),
+            (Some(fix), false) => write!(result, " (fixable: {})", fix).unwrap(),
+            (None, _) => {}
+        }
+        result
+    }
+}
+
+/// Number of single character insertions, deletions or substitutions that turn `a` into `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=b.len()).collect();
+    for (index_a, char_a) in a.chars().enumerate() {
+        let mut row = vec![index_a + 1];
+        for (index_b, char_b) in b.iter().enumerate() {
+            let substitution = previous_row[index_b] + if char_a == *char_b { 0 } else { 1 };
+            let deletion = previous_row[index_b + 1] + 1;
+            let insertion = row[index_b] + 1;
+            row.push(substitution.min(deletion).min(insertion));
+        }
+        previous_row = row;
+    }
+    previous_row[b.len()]
+}
+
+/// Returns the known name that the given unknown name most likely is a typo of. Only names that
+/// differ in case or in at most a quarter of their characters (but no more than two) qualify
+/// and the closest one must be unambiguous. Note that short names like `API` and `APP` can be
+/// meant to be different activities, so only names that differ in case are safe to fix.
+fn find_similar_name<'a>(name: &str, known_names: &[&'a str]) -> Option<&'a str> {
+    let name_lowercase = name.to_lowercase();
+    let max_distance = usize::min(2, name.chars().count() / 4);
+    let mut candidates: Vec<(usize, &str)> = known_names
+        .iter()
+        .map(|known| {
+            (
+                edit_distance(&name_lowercase, &known.to_lowercase()),
+                *known,
+            )
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    candidates.sort_by_key(|(distance, _)| *distance);
+    match candidates.as_slice() {
+        [(_, best)] => Some(best),
+        [(best_distance, best), (second_distance, _), ..] if best_distance < second_distance => {
+            Some(best)
+        }
+        _ => None,
+    }
+}
+
+fn is_same_activity(a: &StampEvent, b: &StampEvent) -> bool {
+    match (a, b) {
+        (StampEvent::Begin(_, name_a), StampEvent::Begin(_, name_b)) => name_a == name_b,
+        (StampEvent::Leave(_), StampEvent::Leave(_)) => true,
+        _ => false,
+    }
+}
+
+/// Checks a single timesheet. Returns the found problems and the fixed timesheet if any of the
+/// problems could be fixed automatically. Timesheets of days before `today` must not end with
+/// an ongoing activity.
+pub fn check_timesheet(
+    location: &str,
+    content: &str,
+    expected_date: Option<NaiveDate>,
+    today: NaiveDate,
+    known_names: &[&str],
+) -> (Vec<Problem>, Option<String>) {
+    let mut problems = Vec::new();
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter(|(_, line)| !line.starts_with("---"));
+
+    let (header_line_number, header) = match lines.next() {
+        Some(header) => header,
+        None => {
+            problems.push(Problem::new(
+                location,
+                None,
+                "The timesheet is empty".to_owned(),
+            ));
+            return (problems, None);
+        }
+    };
+    let date = match NaiveDate::parse_from_str(header, DATE_FORMAT_TIMESHEET) {
+        Ok(date) => Some(date),
+        Err(error) => {
+            problems.push(Problem::new(
+                location,
+                Some(header_line_number),
+                format!("The first line '{}' is not a valid date: {}", header, error),
+            ));
+            None
+        }
+    };
+    if let (Some(date), Some(expected_date)) = (date, expected_date) {
+        if date != expected_date {
+            problems.push(Problem::new(
+                location,
+                Some(header_line_number),
+                format!(
+                    "The date {} does not match the date {} of the timesheet",
+                    date.format("%Y-%m-%d"),
+                    expected_date.format("%Y-%m-%d")
+                ),
+            ));
+        }
+    }
+
+    let mut is_fixable = date.is_some();
+    let mut needs_fixing = false;
+    let mut events: Vec<(usize, StampEvent)> = Vec::new();
+    for (line_number, line) in lines {
+        match StampEvent::from_string(line) {
+            Ok(event) => events.push((line_number, event)),
+            Err(error) => {
+                is_fixable = false;
+                problems.push(Problem::new(location, Some(line_number), error));
+            }
+        }
+    }
+
+    let mut needs_sorting = false;
+    for pair in events.windows(2) {
+        let (_, previous) = &pair[0];
+        let (line_number, event) = &pair[1];
+        if event.timestamp() < previous.timestamp() {
+            needs_sorting = true;
+            problems.push(
+                Problem::new(
+                    location,
+                    Some(*line_number),
+                    format!(
+                        "The stamp '{}' is earlier than the previous stamp at {}",
+                        event.to_string(),
+                        previous.timestamp().to_string()
+                    ),
+                )
+                .with_fix("sort the stamps by time".to_owned()),
+            );
+        } else if event.timestamp() == previous.timestamp() {
+            is_fixable = false;
+            problems.push(Problem::new(
+                location,
+                Some(*line_number),
+                format!(
+                    "The stamp '{}' has the same time as the previous stamp",
+                    event.to_string()
+                ),
+            ));
+        }
+    }
+    if needs_sorting {
+        needs_fixing = true;
+        events.sort_by_key(|(_, event)| event.timestamp());
+    }
+
+    for (line_number, event) in events.iter_mut() {
+        if let StampEvent::Begin(timestamp, name) = event {
+            if name == ACTIVITY_NAME_NON_SPECIFIC_WORK || known_names.contains(&name.as_str()) {
+                continue;
+            }
+            let mut problem = Problem::new(
+                location,
+                Some(*line_number),
+                format!("The activity [{}] is not in the activity list", name),
+            );
+            match find_similar_name(name, known_names) {
+                Some(similar_name) if similar_name.to_lowercase() == name.to_lowercase() => {
+                    needs_fixing = true;
+                    problems.push(problem.with_fix(format!("rename to [{}]", similar_name)));
+                    *event = StampEvent::Begin(*timestamp, similar_name.to_owned());
+                }
+                Some(similar_name) => {
+                    problem.message += &format!(" - did you mean [{}]?", similar_name);
+                    problems.push(problem);
+                }
+                None => problems.push(problem),
+            }
+        }
+    }
+
+    let mut merged_events: Vec<(usize, StampEvent)> = Vec::new();
+    for (line_number, event) in events {
+        if let Some((_, previous)) = merged_events.last() {
+            if is_same_activity(previous, &event) {
+                needs_fixing = true;
+                problems.push(
+                    Problem::new(
+                        location,
+                        Some(line_number),
+                        format!(
+                            "The stamp '{}' repeats the previous activity",
+                            event.to_string()
+                        ),
+                    )
+                    .with_fix("merge with the previous stamp".to_owned()),
+                );
+                continue;
+            }
+        }
+        merged_events.push((line_number, event));
+    }
+
+    if let (Some(date), Some((line_number, StampEvent::Begin(_, name)))) =
+        (date, merged_events.last())
+    {
+        if date < today {
+            problems.push(Problem::new(
+                location,
+                Some(*line_number),
+                format!(
+                    "The day ends with the ongoing activity [{}] which counts until {} - stamp \
+                     a leave where it ended",
+                    name,
+                    TIME_END_OF_DAY.to_string()
+                ),
+            ));
+        }
+    }
+
+    if !is_fixable || !needs_fixing {
+        return (problems, None);
+    }
+    let mut fixed = String::new();
+    writeln!(fixed, "{}", header).unwrap();
+    writeln!(fixed, "------------------------\n").unwrap();
+    for (_, event) in &merged_events {
+        writeln!(fixed, "{}", event.to_string()).unwrap();
+    }
+    // NOTE: Problems we did not anticipate must not be written back
+    if DayEntry::parse_timesheet(&fixed).is_err() {
+        return (problems, None);
+    }
+    (problems, Some(fixed))
+}
+
+/// Checks all timesheets of the database and today's timesheet against each other and the
+/// activity list. Also reports weekdays between the first and the last day of the database that
+/// have no timesheet. If `fix` is true, safe fixes are written back to the storage.
+pub fn check_database(
+    storage: &dyn Storage,
+    activity_list: &[ActivityListEntry],
+    today: NaiveDate,
+    fix: bool,
+) -> Result<Vec<Problem>, String> {
+    let known_names: Vec<&str> = activity_list
+        .iter()
+        .map(|activity| activity.name.as_str())
+        .collect();
+
+    let dates = storage.list_timesheet_dates()?;
+    let mut timesheets: Vec<(Timesheet, Option<NaiveDate>)> = dates
+        .iter()
+        .map(|&date| (Timesheet::Day(date), Some(date)))
+        .collect();
+    timesheets.push((Timesheet::Today, None));
+
+    let mut result = Vec::new();
+    for (timesheet, expected_date) in timesheets {
+        let content = match storage.read_timesheet(timesheet)? {
+            Some(content) => content,
+            None => continue,
+        };
+        let location = storage.timesheet_location(timesheet);
+        let (mut problems, fixed) =
+            check_timesheet(&location, &content, expected_date, today, &known_names);
+        if let (true, Some(fixed)) = (fix, fixed) {
+            storage.write_timesheet(timesheet, &fixed)?;
+            for problem in problems.iter_mut().filter(|problem| problem.fix.is_some()) {
+                problem.is_fixed = true;
+            }
+        }
+        result.extend(problems);
+    }
+
+    if let (Some(&first), Some(&last)) = (dates.first(), dates.last()) {
+        let mut date = first;
+        while date < last {
+            let is_weekend = date.weekday() == Weekday::Sat || date.weekday() == Weekday::Sun;
+            if !is_weekend && dates.binary_search(&date).is_err() {
+                result.push(Problem::new(
+                    &storage.timesheet_location(Timesheet::Day(date)),
+                    None,
+                    format!("There is no timesheet for {}", date.format("%A %Y-%m-%d")),
+                ));
+            }
+            date = date.succ();
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    const KNOWN_NAMES: [&str; 3] = ["Coding", "Learning", "Meeting"];
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd(2021, 3, day)
+    }
+
+    fn check(content: &str) -> (Vec<Problem>, Option<String>) {
+        check_timesheet(
+            "friday.txt",
+            content,
+            Some(date(12)),
+            date(12),
+            &KNOWN_NAMES,
+        )
+    }
+
+    fn messages(problems: &[Problem]) -> Vec<String> {
+        problems.iter().map(Problem::to_string).collect()
+    }
+
+    #[test]
+    fn edit_distance_counts_single_character_changes() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("Coding", "Coding"), 0);
+        assert_eq!(edit_distance("Coding", "Cding"), 1);
+        assert_eq!(edit_distance("Coding", "Codding"), 1);
+        assert_eq!(edit_distance("Coding", "Coting"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn find_similar_name_needs_a_close_and_unambiguous_match() {
+        assert_eq!(find_similar_name("meeting", &KNOWN_NAMES), Some("Meeting"));
+        assert_eq!(find_similar_name("Lerning", &KNOWN_NAMES), Some("Learning"));
+        // Short names only match if they differ in case
+        assert_eq!(find_similar_name("Cod", &["Cad"]), None);
+        assert_eq!(find_similar_name("cad", &["Cad"]), Some("Cad"));
+        assert_eq!(find_similar_name("Reading", &KNOWN_NAMES), None);
+        assert_eq!(find_similar_name("Tasks", &["Task", "Tasky"]), None);
+    }
+
+    #[test]
+    fn check_timesheet_accepts_a_valid_timesheet() {
+        let (problems, fixed) = check(
+            "Timesheet for 2021-03-12\n\
+             ------------------------\n\
+             \n\
+             08:00 - Begin [Coding]\n\
+             12:00 - Leave\n\
+             12:30 - Begin [Work (Non-specific)]\n",
+        );
+        assert!(problems.is_empty(), "{:?}", messages(&problems));
+        assert_eq!(fixed, None);
+    }
+
+    #[test]
+    fn check_timesheet_fixes_order_repeats_and_typos() {
+        let (problems, fixed) = check(
+            "Timesheet for 2021-03-12\n\
+             08:00 - Begin [Coding]\n\
+             10:00 - Begin [meeting]\n\
+             09:00 - Begin [Coding]\n\
+             12:00 - Leave\n",
+        );
+        assert_eq!(
+            messages(&problems),
+            vec![
+                "friday.txt:4: The stamp '09:00 - Begin [Coding]' is earlier than the previous stamp at 10:00 (fixable: sort the stamps by time)",
+                "friday.txt:3: The activity [meeting] is not in the activity list (fixable: rename to [Meeting])",
+                "friday.txt:4: The stamp '09:00 - Begin [Coding]' repeats the previous activity (fixable: merge with the previous stamp)",
+            ]
+        );
+        assert_eq!(
+            fixed.as_deref(),
+            Some(
+                "Timesheet for 2021-03-12\n\
+                 ------------------------\n\
+                 \n\
+                 08:00 - Begin [Coding]\n\
+                 10:00 - Begin [Meeting]\n\
+                 12:00 - Leave\n"
+            )
+        );
+
+        let (problems, fixed) = check(
+            "Timesheet for 2021-03-12\n\
+             08:00 - Begin [Coding]\n\
+             09:00 - Begin [Coding]\n",
+        );
+        assert_eq!(
+            messages(&problems),
+            vec!["friday.txt:3: The stamp '09:00 - Begin [Coding]' repeats the previous activity (fixable: merge with the previous stamp)"]
+        );
+        assert_eq!(
+            fixed.as_deref(),
+            Some(
+                "Timesheet for 2021-03-12\n\
+                 ------------------------\n\
+                 \n\
+                 08:00 - Begin [Coding]\n"
+            )
+        );
+    }
+
+    #[test]
+    fn check_timesheet_only_suggests_names_that_differ_in_more_than_case() {
+        let (problems, fixed) = check(
+            "Timesheet for 2021-03-12\n\
+             08:00 - Begin [Lerning]\n",
+        );
+        assert_eq!(
+            messages(&problems),
+            vec!["friday.txt:2: The activity [Lerning] is not in the activity list - did you mean [Learning]?"]
+        );
+        assert_eq!(fixed, None);
+    }
+
+    #[test]
+    fn check_timesheet_does_not_fix_broken_timesheets() {
+        let (problems, fixed) = check("");
+        assert_eq!(
+            messages(&problems),
+            vec!["friday.txt: The timesheet is empty"]
+        );
+        assert_eq!(fixed, None);
+
+        let (problems, fixed) = check(
+            "Timesheet for 2021-03-11\n\
+             08:00 - Begin [Coding]\n\
+             08:00 - Leave\n\
+             09:00 - Begin\n\
+             10:00 - Begin [meeting]\n",
+        );
+        assert_eq!(
+            messages(&problems),
+            vec![
+                "friday.txt:1: The date 2021-03-11 does not match the date 2021-03-12 of the timesheet",
+                "friday.txt:4: The string '09:00 - Begin' is not a valid stamp event",
+                "friday.txt:3: The stamp '08:00 - Leave' has the same time as the previous stamp",
+                "friday.txt:5: The activity [meeting] is not in the activity list (fixable: rename to [Meeting])",
+                "friday.txt:5: The day ends with the ongoing activity [Meeting] which counts until 23:59 - stamp a leave where it ended",
+            ]
+        );
+        assert_eq!(fixed, None);
+    }
+
+    #[test]
+    fn check_timesheet_reports_ongoing_activities_at_the_end_of_past_days() {
+        let content = "Timesheet for 2021-03-12\n\
+                       08:00 - Begin [Coding]\n\
+                       12:00 - Leave\n\
+                       13:00 - Begin [Meeting]\n";
+        let (problems, _) = check_timesheet("friday.txt", content, None, date(12), &KNOWN_NAMES);
+        assert!(problems.is_empty(), "{:?}", messages(&problems));
+
+        let (problems, fixed) =
+            check_timesheet("friday.txt", content, None, date(15), &KNOWN_NAMES);
+        assert_eq!(
+            messages(&problems),
+            vec!["friday.txt:4: The day ends with the ongoing activity [Meeting] which counts until 23:59 - stamp a leave where it ended"]
+        );
+        assert_eq!(fixed, None);
+    }
+
+    #[test]
+    fn check_database_reports_missing_weekdays_and_writes_back_fixes() {
+        let storage = MemoryStorage::new();
+        let activity_list: Vec<ActivityListEntry> = KNOWN_NAMES
+            .iter()
+            .map(|name| ActivityListEntry::from_string(name).unwrap())
+            .collect();
+        // Friday and the following Tuesday with a weekend and a missing Monday in between
+        storage
+            .write_timesheet(
+                Timesheet::Day(date(12)),
+                "Timesheet for 2021-03-12\n08:00 - Begin [coding]\n17:00 - Leave\n",
+            )
+            .unwrap();
+        storage
+            .write_timesheet(
+                Timesheet::Day(date(16)),
+                "Timesheet for 2021-03-16\n08:00 - Begin [Coding]\n",
+            )
+            .unwrap();
+
+        let problems = check_database(&storage, &activity_list, date(16), false).unwrap();
+        assert_eq!(
+            messages(&problems),
+            vec![
+                "memory:2021-03-12:2: The activity [coding] is not in the activity list (fixable: rename to [Coding])",
+                "memory:2021-03-15: There is no timesheet for Monday 2021-03-15",
+            ]
+        );
+        assert!(storage
+            .read_timesheet(Timesheet::Day(date(12)))
+            .unwrap()
+            .unwrap()
+            .contains("[coding]"));
+
+        let problems = check_database(&storage, &activity_list, date(16), true).unwrap();
+        assert!(problems[0].is_fixed);
+        assert!(storage
+            .read_timesheet(Timesheet::Day(date(12)))
+            .unwrap()
+            .unwrap()
+            .contains("08:00 - Begin [Coding]"));
+        assert_eq!(
+            check_database(&storage, &activity_list, date(16), true)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
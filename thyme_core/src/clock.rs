@@ -0,0 +1,59 @@
+use chrono::prelude::*;
+
+use std::sync::{Arc, Mutex};
+
+/// Source of the current date and time. Everything that depends on the current time asks a
+/// clock instead of the system so that days can be replayed with a `FakeClock`.
+pub trait Clock {
+    fn now(&self) -> NaiveDateTime;
+
+    fn today(&self) -> NaiveDate {
+        self.now().date()
+    }
+}
+
+/// A clock that can be shared between day entries and threads
+pub type SharedClock = Arc<dyn Clock + Send + Sync>;
+
+/// The local time of the system
+pub struct RealClock;
+
+impl RealClock {
+    pub fn shared() -> SharedClock {
+        Arc::new(RealClock)
+    }
+}
+
+impl Clock for RealClock {
+    fn now(&self) -> NaiveDateTime {
+        Local::now().naive_local()
+    }
+}
+
+/// A clock that only moves when told to
+pub struct FakeClock {
+    now: Mutex<NaiveDateTime>,
+}
+
+impl FakeClock {
+    pub fn new(now: NaiveDateTime) -> FakeClock {
+        FakeClock {
+            now: Mutex::new(now),
+        }
+    }
+
+    pub fn set(&self, now: NaiveDateTime) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    pub fn advance(&self, duration: chrono::Duration) {
+        let mut now = self.now.lock().unwrap();
+        *now += duration;
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> NaiveDateTime {
+        *self.now.lock().unwrap()
+    }
+}
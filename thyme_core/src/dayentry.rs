@@ -0,0 +1,1598 @@
+use crate::activitylist::ActivityListEntry;
+use crate::budget::{compare_plan_with_actual, write_plan_vs_actual, WeekDurationsCache};
+use crate::clock::SharedClock;
+use crate::gitlog::{GitActivityLog, GitRepository};
+use crate::report_html::{generate_day_report_html, generate_week_report_html};
+use crate::report_markdown::generate_day_report_markdown;
+use crate::rounding::RoundingPolicy;
+use crate::storage::{Report, SharedStorage, Timesheet};
+use crate::time::{DateTimeHelper, TimeDuration, TimeStamp};
+
+use ct_lib_core::indexmap::IndexMap;
+
+use chrono::prelude::*;
+
+use std::{collections::HashSet, fmt::Write, sync::Arc};
+
+pub const ACTIVITY_NAME_NON_SPECIFIC_WORK: &str = "Work (Non-specific)";
+pub const ACTIVITY_NAME_LEAVE: &str = "Leave";
+pub const ACTIVITY_NAME_BREAK: &str = "Break";
+
+/// Format of the first line of every timesheet
+pub const DATE_FORMAT_TIMESHEET: &str = "Timesheet for %Y-%m-%d";
+/// The last minute of a day that a stamp can have
+pub const TIME_END_OF_DAY: TimeStamp = TimeStamp {
+    hours: 23,
+    minutes: 59,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReportFormat {
+    Text,
+    Markdown,
+}
+
+impl ReportFormat {
+    pub fn from_string(input: &str) -> Option<ReportFormat> {
+        match input {
+            "text" | "txt" => Some(ReportFormat::Text),
+            "markdown" | "md" => Some(ReportFormat::Markdown),
+            _ => None,
+        }
+    }
+
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Text => "txt",
+            ReportFormat::Markdown => "md",
+        }
+    }
+}
+
+/// Everything besides the timesheets and the activity list that influences the daily reports
+#[derive(Debug, Clone)]
+pub struct ReportSettings {
+    pub format: ReportFormat,
+    pub rounding: RoundingPolicy,
+    /// Reports show a warning if the share of non-specific work exceeds this percentage
+    /// (0 = never)
+    pub non_specific_warning_percent: u32,
+    /// Repositories whose commits are listed under the activities during which they were made
+    pub git_repositories: Vec<GitRepository>,
+    /// Only commits of this author are listed. Defaults to the `user.email` of each repository.
+    pub git_author: Option<String>,
+    /// Days with more work than this count as over target in the statistics
+    pub target_work_duration: TimeDuration,
+}
+
+impl Default for ReportSettings {
+    fn default() -> ReportSettings {
+        ReportSettings {
+            format: ReportFormat::Text,
+            rounding: RoundingPolicy::exact(),
+            non_specific_warning_percent: 25,
+            git_repositories: Vec::new(),
+            git_author: None,
+            target_work_duration: TimeDuration { minutes: 8 * 60 },
+        }
+    }
+}
+
+pub fn write_durations_summary(day_entry: &DayEntry) -> String {
+    let mut result = String::new();
+
+    let work_duration_total = day_entry.get_work_duration_total();
+    let work_duration_activities = day_entry.get_work_duration_specific();
+    let work_duration_non_specific = day_entry.get_work_duration_non_specific();
+    let work_percent_specific = (100.0
+        * (work_duration_activities.minutes as f32 / work_duration_total.minutes as f32))
+        .round() as usize;
+    let work_percent_non_specific = 100 - work_percent_specific;
+    writeln!(
+        result,
+        "Total work duration:            {} (100%)",
+        work_duration_total.to_string(),
+    )
+    .unwrap();
+    writeln!(
+        result,
+        "  - Activities (from list):     {} ({: >3}%)",
+        work_duration_activities.to_string(),
+        work_percent_specific
+    )
+    .unwrap();
+    writeln!(
+        result,
+        "  - Activities (non-specific):  {} ({: >3}%)",
+        work_duration_non_specific.to_string(),
+        work_percent_non_specific
+    )
+    .unwrap();
+    writeln!(
+        result,
+        "Total break duration:           {}",
+        day_entry.get_break_duration().to_string(),
+    )
+    .unwrap();
+    if let Some(leave_duration) = day_entry.get_leave_duration() {
+        writeln!(
+            result,
+            "Time since last leave:          {}",
+            leave_duration.to_string(),
+        )
+        .unwrap();
+    } else {
+        writeln!(result).unwrap();
+    }
+
+    result
+}
+
+/// Returns a warning if the share of non-specific work exceeds the threshold given in the settings
+pub fn write_non_specific_work_warning(
+    day_entry: &DayEntry,
+    settings: &ReportSettings,
+) -> Option<String> {
+    let work_duration_total = day_entry.get_work_duration_total();
+    if settings.non_specific_warning_percent == 0 || work_duration_total.minutes <= 0 {
+        return None;
+    }
+    let work_percent_non_specific = (100.0
+        * (day_entry.get_work_duration_non_specific().minutes as f32
+            / work_duration_total.minutes as f32))
+        .round() as u32;
+    if work_percent_non_specific <= settings.non_specific_warning_percent {
+        return None;
+    }
+    Some(format!(
+        "Warning: {}% of the work time is non-specific (more than {}%) - use 'thyme reclassify {}' to assign it to activities",
+        work_percent_non_specific,
+        settings.non_specific_warning_percent,
+        day_entry.date.format("%Y-%m-%d")
+    ))
+}
+
+#[derive(Clone)]
+pub struct DayEntry {
+    pub date: NaiveDate,
+    pub activities: Vec<Activity>,
+    pub last_write_time: f64,
+    /// Determines the end of ongoing activities and the time at which new activities start
+    pub clock: SharedClock,
+    /// Where the timesheet is written back to and the reports are written to
+    pub storage: SharedStorage,
+    /// The activity durations of the previous days of the week, shared between clones
+    week_durations_before: Arc<WeekDurationsCache>,
+}
+
+impl DayEntry {
+    pub fn create_empty(clock: &SharedClock, storage: &SharedStorage) -> Result<DayEntry, String> {
+        let mut result = DayEntry {
+            activities: vec![],
+            date: clock.today(),
+            last_write_time: 0.0,
+            clock: clock.clone(),
+            storage: storage.clone(),
+            week_durations_before: Arc::default(),
+        };
+        result.write_back()?;
+        Ok(result)
+    }
+
+    /// Loads today's timesheet or starts a new day with non-specific work if there is none yet
+    pub fn load_or_create(
+        clock: &SharedClock,
+        storage: &SharedStorage,
+    ) -> Result<DayEntry, String> {
+        let today_date = clock.today();
+        let today_entry = DayEntry {
+            activities: vec![Activity {
+                is_work: true,
+                name: ACTIVITY_NAME_NON_SPECIFIC_WORK.to_owned(),
+                time_start: clock.now().to_timestamp(),
+                time_end: None,
+            }],
+            date: today_date,
+            last_write_time: 0.0,
+            clock: clock.clone(),
+            storage: storage.clone(),
+            week_durations_before: Arc::default(),
+        };
+
+        let mut result = match DayEntry::load_timesheet(Timesheet::Today, clock, storage)? {
+            Some(entry) if entry.date == today_date => entry,
+            _ => today_entry,
+        };
+
+        result.write_back()?;
+        Ok(result)
+    }
+
+    /// Returns true if the timesheet was changed externally and got reloaded
+    pub fn hotreload_external_changes(&mut self) -> Result<bool, String> {
+        let last_modified_time = self
+            .storage
+            .timesheet_modified_time(Timesheet::Today)
+            .ok_or_else(|| {
+                format!(
+                    "The timesheet '{}' is missing",
+                    self.storage.timesheet_location(Timesheet::Today)
+                )
+            })?;
+        if self.last_write_time < last_modified_time {
+            if let Some(day_entry) =
+                DayEntry::load_timesheet(Timesheet::Today, &self.clock, &self.storage)?
+            {
+                *self = DayEntry {
+                    week_durations_before: self.week_durations_before.clone(),
+                    ..day_entry
+                };
+                self.write_back()?;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    pub fn load_from_database(
+        date: NaiveDate,
+        clock: &SharedClock,
+        storage: &SharedStorage,
+    ) -> Result<Option<DayEntry>, String> {
+        DayEntry::load_timesheet(Timesheet::Day(date), clock, storage)
+    }
+
+    /// Loads all days between the given dates (inclusive) that have a timesheet in the database
+    pub fn load_range_from_database(
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        clock: &SharedClock,
+        storage: &SharedStorage,
+    ) -> Result<Vec<DayEntry>, String> {
+        let mut result = Vec::new();
+        let mut date = date_from;
+        while date <= date_to {
+            if let Some(day_entry) = DayEntry::load_from_database(date, clock, storage)? {
+                result.push(day_entry);
+            }
+            date = date.succ();
+        }
+        Ok(result)
+    }
+
+    /// Loads all days of the database in ascending order
+    pub fn load_all_from_database(
+        clock: &SharedClock,
+        storage: &SharedStorage,
+    ) -> Result<Vec<DayEntry>, String> {
+        let mut result = Vec::new();
+        for date in storage.list_timesheet_dates()? {
+            if let Some(day_entry) = DayEntry::load_from_database(date, clock, storage)? {
+                result.push(day_entry);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Finds the closest day before the given date that has a timesheet in the database.
+    /// Looks back at most two weeks.
+    pub fn load_previous_from_database(
+        date: NaiveDate,
+        clock: &SharedClock,
+        storage: &SharedStorage,
+    ) -> Result<Option<DayEntry>, String> {
+        let mut previous_date = date;
+        for _ in 0..14 {
+            previous_date = previous_date.pred();
+            if let Some(day_entry) = DayEntry::load_from_database(previous_date, clock, storage)? {
+                return Ok(Some(day_entry));
+            }
+        }
+        Ok(None)
+    }
+
+    fn load_timesheet(
+        timesheet: Timesheet,
+        clock: &SharedClock,
+        storage: &SharedStorage,
+    ) -> Result<Option<DayEntry>, String> {
+        match storage.read_timesheet(timesheet)? {
+            Some(content) => DayEntry::from_timesheet_string(&content, clock, storage)
+                .map(Some)
+                .map_err(|error| {
+                    format!(
+                        "Invalid timesheet '{}' - {}",
+                        storage.timesheet_location(timesheet),
+                        error
+                    )
+                }),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses a timesheet as written by `to_timesheet_string`
+    pub fn from_timesheet_string(
+        input: &str,
+        clock: &SharedClock,
+        storage: &SharedStorage,
+    ) -> Result<DayEntry, String> {
+        let (date, activities) = DayEntry::parse_timesheet(input)?;
+        Ok(DayEntry {
+            date,
+            activities,
+            last_write_time: 0.0,
+            clock: clock.clone(),
+            storage: storage.clone(),
+            week_durations_before: Arc::default(),
+        })
+    }
+
+    /// Returns the date and the activities of a timesheet as written by `timesheet_to_string`
+    pub fn parse_timesheet(input: &str) -> Result<(NaiveDate, Vec<Activity>), String> {
+        let mut lines: Vec<&str> = input
+            .lines()
+            .filter(|line| !line.is_empty())
+            .filter(|line| !line.starts_with("---"))
+            .collect();
+        if lines.is_empty() {
+            return Err("Found empty timesheet".to_owned());
+        }
+
+        let first_line = lines.remove(0);
+        let date = NaiveDate::parse_from_str(first_line, DATE_FORMAT_TIMESHEET).map_err(|err| {
+            format!(
+                "First line of timesheet '{}' is not a valid date: {}",
+                first_line, err
+            )
+        })?;
+
+        let stamp_events: Vec<StampEvent> = lines
+            .into_iter()
+            .map(StampEvent::from_string)
+            .collect::<Result<_, _>>()?;
+
+        // Check if stamps are in correct order
+        let mut previous = TimeStamp {
+            hours: 0,
+            minutes: 0,
+        };
+        for event in &stamp_events {
+            let timestamp = event.timestamp();
+            if timestamp <= previous {
+                return Err(format!(
+                    "Found stamp event '{}' that begins earlier than previous event in list at {}",
+                    event.to_string(),
+                    previous.to_string()
+                ));
+            }
+            previous = timestamp;
+        }
+
+        let activities = DayEntry::create_activities_from_stamp_events(&stamp_events)?;
+        Ok((date, activities))
+    }
+
+    pub fn write_back(&mut self) -> Result<(), String> {
+        self.write_timesheets()
+    }
+
+    /// Writes the timesheet to the database. The timesheet of today is additionally written to
+    /// the working copy of today (i.e. 'today__timesheet.txt').
+    pub fn write_timesheets(&mut self) -> Result<(), String> {
+        let timesheet = self.to_timesheet_string();
+
+        let timesheet_database = Timesheet::Day(self.date);
+        self.storage
+            .write_timesheet(timesheet_database, &timesheet)?;
+        self.last_write_time = self
+            .storage
+            .timesheet_modified_time(timesheet_database)
+            .unwrap_or(0.0);
+
+        // NOTE: We need to make sure that editing older days does not overwrite today's file
+        if self.is_today() {
+            self.storage.write_timesheet(Timesheet::Today, &timesheet)?;
+            self.last_write_time = self
+                .storage
+                .timesheet_modified_time(Timesheet::Today)
+                .unwrap_or(0.0);
+        }
+        Ok(())
+    }
+
+    pub fn is_today(&self) -> bool {
+        self.date == self.clock.today()
+    }
+
+    /// Sums up the activity durations of the days of this week before this day. The days are
+    /// only loaded again if one of their timesheets changed since the last call.
+    pub fn load_week_durations_before(&self) -> Result<IndexMap<String, TimeDuration>, String> {
+        self.week_durations_before
+            .load_before(self.date, &self.clock, &self.storage)
+    }
+
+    /// Returns the current time of day according to the clock of this entry
+    pub fn time_now(&self) -> TimeStamp {
+        self.clock.now().to_timestamp()
+    }
+
+    /// Returns the time until which ongoing activities last. This is the current time for today.
+    /// On days in the past work that was never stamped out lasts until the end of the day, just
+    /// like it would have if thyme kept running. A past day that ends with leaving ends with its
+    /// last stamp.
+    pub fn time_end_of_entry(&self) -> TimeStamp {
+        if self.is_today() {
+            return self.time_now();
+        }
+        match self.activities.last() {
+            Some(activity) if activity.is_work && activity.time_end.is_none() => TIME_END_OF_DAY,
+            Some(activity) => activity.time_end.unwrap_or(activity.time_start),
+            None => TimeStamp::from_minutes_of_day(0),
+        }
+    }
+
+    /// Stamps a leave at the end of the day if there is still work ongoing. This closes the
+    /// day when midnight passes so that its timesheet shows when the work ended.
+    pub fn leave_at_end_of_day(&mut self) {
+        if let Some(current) = self.get_current_activity_mut() {
+            if !current.is_work {
+                return;
+            }
+            current.time_end = Some(TIME_END_OF_DAY);
+        } else {
+            return;
+        }
+
+        self.activities.push(Activity {
+            is_work: false,
+            name: ACTIVITY_NAME_LEAVE.to_owned(),
+            time_start: TIME_END_OF_DAY,
+            time_end: None,
+        });
+        DayEntry::cleanup_activities(&mut self.activities);
+    }
+
+    /// Formats the given activity of this day. On days in the past an ongoing activity is shown
+    /// with the time it lasts until.
+    pub fn activity_to_string(&self, activity: &Activity) -> String {
+        let time_end = self.time_end_of_entry();
+        if self.is_today() || activity.time_end.is_some() {
+            return activity.to_string(time_end);
+        }
+        Activity {
+            time_end: Some(time_end),
+            ..activity.clone()
+        }
+        .to_string(time_end)
+    }
+
+    pub fn to_timesheet_string(&self) -> String {
+        DayEntry::timesheet_to_string(self.date, &self.activities)
+    }
+
+    pub fn timesheet_to_string(date: NaiveDate, activities: &[Activity]) -> String {
+        let mut result = String::new();
+
+        writeln!(result, "{}", date.format(DATE_FORMAT_TIMESHEET)).unwrap();
+        writeln!(result, "------------------------\n").unwrap();
+        let stamp_events = DayEntry::create_stamp_events_from_activities(activities);
+        for stamp_event in &stamp_events {
+            writeln!(result, "{}", stamp_event.to_string()).unwrap();
+        }
+        result
+    }
+
+    /// Writes the reports of this day and the report of its week
+    pub fn write_report(
+        &self,
+        settings: &ReportSettings,
+        activity_list: &[ActivityListEntry],
+    ) -> Result<(), String> {
+        self.write_day_reports(settings, activity_list)?;
+        self.write_week_report(settings, activity_list)
+    }
+
+    /// Writes the report of this day in the configured format and as HTML. Reports of this day
+    /// in the other format are removed so that no outdated one is left behind.
+    pub fn write_day_reports(
+        &self,
+        settings: &ReportSettings,
+        activity_list: &[ActivityListEntry],
+    ) -> Result<(), String> {
+        let report = self.generate_report_in_format(settings, activity_list, settings.format)?;
+        let extension = settings.format.file_extension();
+
+        self.storage
+            .write_report(Report::Day(self.date, extension), &report)?;
+        if self.is_today() {
+            self.storage
+                .write_report(Report::Today(extension), &report)?;
+        }
+
+        for format in [ReportFormat::Text, ReportFormat::Markdown] {
+            if format == settings.format {
+                continue;
+            }
+            let extension = format.file_extension();
+            self.storage
+                .remove_report(Report::Day(self.date, extension))?;
+            if self.is_today() {
+                self.storage.remove_report(Report::Today(extension))?;
+            }
+        }
+
+        let report_html = generate_day_report_html(self);
+        self.storage
+            .write_report(Report::Day(self.date, "html"), &report_html)
+    }
+
+    /// Writes the HTML report for the week of this day. The other days of the week are read
+    /// from the database.
+    pub fn write_week_report(
+        &self,
+        settings: &ReportSettings,
+        activity_list: &[ActivityListEntry],
+    ) -> Result<(), String> {
+        let week_start =
+            self.date - chrono::Duration::days(self.date.weekday().num_days_from_monday() as i64);
+        let week_end = week_start + chrono::Duration::days(6);
+        let mut day_entries =
+            DayEntry::load_range_from_database(week_start, week_end, &self.clock, &self.storage)?;
+        day_entries.retain(|day_entry| day_entry.date != self.date);
+        day_entries.push(self.clone());
+        day_entries.sort_by_key(|day_entry| day_entry.date);
+
+        let report = generate_week_report_html(self.date, &day_entries, activity_list, settings);
+        let week = self.date.iso_week();
+        self.storage
+            .write_report(Report::Week(week.year(), week.week()), &report)
+    }
+
+    /// Generates the daily report. Fails if the other days of the week that are needed for
+    /// weekly budgets cannot be read from the database.
+    pub fn generate_report_in_format(
+        &self,
+        settings: &ReportSettings,
+        activity_list: &[ActivityListEntry],
+        format: ReportFormat,
+    ) -> Result<String, String> {
+        match format {
+            ReportFormat::Text => self.generate_report(settings, activity_list),
+            ReportFormat::Markdown => generate_day_report_markdown(self, settings, activity_list),
+        }
+    }
+
+    fn generate_report(
+        &self,
+        settings: &ReportSettings,
+        activity_list: &[ActivityListEntry],
+    ) -> Result<String, String> {
+        let mut result = String::new();
+        let checkin_date = self.date;
+
+        writeln!(
+            result,
+            "Report for {}\n",
+            checkin_date.format("%A %e. %b (%d.%m.%Y)"),
+        )
+        .unwrap();
+
+        writeln!(result, "\nActivity Durations:").unwrap();
+        writeln!(result, "=====================\n").unwrap();
+
+        for (activity_name, duration) in self.get_activity_durations().into_iter() {
+            writeln!(result, "{} - {}", duration.to_string(), activity_name).unwrap();
+        }
+
+        // Totals summary
+        writeln!(result, "\n-------------\n").unwrap();
+
+        writeln!(result, "{}", &write_durations_summary(self)).unwrap();
+        if let Some(warning) = write_non_specific_work_warning(self, settings) {
+            writeln!(result, "{}\n", warning).unwrap();
+        }
+
+        // Planned budgets
+        write!(
+            result,
+            "{}",
+            write_plan_vs_actual(&compare_plan_with_actual(self, activity_list)?)
+        )
+        .unwrap();
+
+        // Rounded durations
+        if !settings.rounding.is_exact() {
+            writeln!(
+                result,
+                "\nRounded Activity Durations ({}):",
+                settings.rounding.to_string()
+            )
+            .unwrap();
+            writeln!(result, "=====================\n").unwrap();
+
+            let activity_durations = self.get_activity_durations();
+            for (activity_name, duration_rounded) in settings
+                .rounding
+                .get_activity_durations_rounded(self)
+                .into_iter()
+            {
+                writeln!(
+                    result,
+                    "{} (exact {}) - {}",
+                    duration_rounded.to_string(),
+                    activity_durations[&activity_name].to_string(),
+                    activity_name
+                )
+                .unwrap();
+            }
+            writeln!(result, "\n-------------\n").unwrap();
+            writeln!(
+                result,
+                "Total work duration (exact):    {}",
+                self.get_work_duration_total().to_string(),
+            )
+            .unwrap();
+            writeln!(
+                result,
+                "Total work duration (rounded):  {}\n",
+                settings
+                    .rounding
+                    .get_work_duration_total_rounded(self)
+                    .to_string(),
+            )
+            .unwrap();
+        }
+
+        // Activity list
+        let git_log = GitActivityLog::collect(
+            self,
+            &settings.git_repositories,
+            settings.git_author.as_deref(),
+        );
+        writeln!(result, "\nDetailed Activity List:").unwrap();
+        writeln!(result, "=========================\n").unwrap();
+        for (index, activity) in self.activities.iter().enumerate() {
+            writeln!(result, "{}", self.activity_to_string(activity)).unwrap();
+            for commit in &git_log.commits_per_activity[index] {
+                writeln!(result, "        {}", commit.to_string()).unwrap();
+            }
+            if let Some((activity_name, commit_count)) =
+                git_log.suggest_activity_name(index, activity)
+            {
+                writeln!(
+                    result,
+                    "        -> Suggested activity: [{}] ({} commits)",
+                    activity_name, commit_count
+                )
+                .unwrap();
+            }
+        }
+        writeln!(result).unwrap();
+        for error in &git_log.errors {
+            writeln!(result, "Warning: {}", error).unwrap();
+        }
+
+        Ok(result)
+    }
+
+    /// Ends the current activity and starts the given one. The caller is responsible for
+    /// writing back the changed timesheet.
+    pub fn start_activitiy(&mut self, name: &str, is_work: bool) {
+        let timestamp_now = self.time_now();
+
+        // Close previous activity
+        if let Some(current) = self.get_current_activity_mut() {
+            if current.name == name {
+                debug_assert!(false, "Trying to start same activity '{}' twice", name);
+                return;
+            }
+            assert!(current.time_end.is_none());
+            current.time_end = Some(timestamp_now);
+        }
+
+        // Start new activity
+        self.activities.push(Activity {
+            is_work,
+            name: name.to_owned(),
+            time_start: timestamp_now,
+            time_end: None,
+        });
+
+        DayEntry::cleanup_activities(&mut self.activities);
+    }
+
+    /// Returns the indices of all non-specific work activities
+    pub fn get_non_specific_work_indices(&self) -> Vec<usize> {
+        self.activities
+            .iter()
+            .enumerate()
+            .filter(|(_index, activity)| {
+                activity.is_work && activity.name == ACTIVITY_NAME_NON_SPECIFIC_WORK
+            })
+            .map(|(index, _activity)| index)
+            .collect()
+    }
+
+    /// Replaces the non-specific work activity at the given index with consecutive activities
+    /// of the given names and durations. A part without a duration gets the rest of the
+    /// interval and can only be the last part. If the parts do not fill the whole interval the
+    /// rest stays non-specific.
+    pub fn reassign_non_specific_work(
+        &mut self,
+        index: usize,
+        parts: &[(String, Option<TimeDuration>)],
+    ) -> Result<(), String> {
+        let activity = self
+            .activities
+            .get(index)
+            .filter(|activity| activity.is_work && activity.name == ACTIVITY_NAME_NON_SPECIFIC_WORK)
+            .cloned()
+            .ok_or_else(|| "The selected interval is not non-specific work".to_owned())?;
+        if parts.is_empty() {
+            return Err("No activities given".to_owned());
+        }
+
+        let start = activity.time_start.to_minutes_of_day();
+        let end = start + activity.duration(self.time_end_of_entry()).minutes;
+        let mut replacements = Vec::new();
+        let mut current = start;
+        for (part_index, (name, duration)) in parts.iter().enumerate() {
+            if name.trim().is_empty() || name.contains('[') || name.contains(']') {
+                return Err(format!("Invalid activity name '{}'", name));
+            }
+            let part_end = match duration {
+                Some(duration) => current + duration.minutes,
+                None if part_index + 1 == parts.len() => end,
+                None => {
+                    return Err(format!(
+                        "Activity '{}' needs a duration as it is not the last one",
+                        name
+                    ))
+                }
+            };
+            if part_end > end {
+                return Err(format!(
+                    "The given durations exceed the interval {} - {} ({})",
+                    activity.time_start.to_string(),
+                    TimeStamp::from_minutes_of_day(end).to_string(),
+                    activity.duration(self.time_end_of_entry()).to_string()
+                ));
+            }
+            replacements.push(Activity {
+                is_work: true,
+                name: name.trim().to_owned(),
+                time_start: TimeStamp::from_minutes_of_day(current),
+                time_end: Some(TimeStamp::from_minutes_of_day(part_end)),
+            });
+            current = part_end;
+        }
+        if current < end {
+            replacements.push(Activity {
+                is_work: true,
+                name: ACTIVITY_NAME_NON_SPECIFIC_WORK.to_owned(),
+                time_start: TimeStamp::from_minutes_of_day(current),
+                time_end: Some(TimeStamp::from_minutes_of_day(end)),
+            });
+        }
+        // NOTE: An ongoing activity needs to stay ongoing
+        if activity.time_end.is_none() {
+            if let Some(last) = replacements.last_mut() {
+                last.time_end = None;
+            }
+        }
+
+        self.activities.splice(index..=index, replacements);
+        DayEntry::cleanup_activities(&mut self.activities);
+        Ok(())
+    }
+
+    /// Assigns the work activity at the given index to the activity with the given name. Adjacent
+    /// activities that end up with the same name are merged.
+    pub fn reassign_activity(&mut self, index: usize, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() || name.contains('[') || name.contains(']') {
+            return Err(format!("Invalid activity name '{}'", name));
+        }
+        let activity = self
+            .activities
+            .get_mut(index)
+            .filter(|activity| activity.is_work)
+            .ok_or_else(|| "The selected interval is not work".to_owned())?;
+        activity.name = name.to_owned();
+        DayEntry::cleanup_activities(&mut self.activities);
+        Ok(())
+    }
+
+    /// Renames all work activities with one of the given old names. Adjacent activities that end
+    /// up with the same name are merged. Returns the renamed duration per old name, which is
+    /// empty if nothing was renamed.
+    pub fn rename_activities(
+        &mut self,
+        old_names: &[String],
+        new_name: &str,
+    ) -> Result<IndexMap<String, TimeDuration>, String> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() || new_name.contains('[') || new_name.contains(']') {
+            return Err(format!("Invalid activity name '{}'", new_name));
+        }
+
+        let now = self.time_end_of_entry();
+        let mut result: IndexMap<String, TimeDuration> = IndexMap::new();
+        for activity in self.activities.iter_mut() {
+            if activity.is_work && activity.name != new_name && old_names.contains(&activity.name) {
+                *result
+                    .entry(activity.name.clone())
+                    .or_insert(TimeDuration::zero()) += activity.duration(now);
+                activity.name = new_name.to_owned();
+            }
+        }
+        DayEntry::cleanup_activities(&mut self.activities);
+        Ok(result)
+    }
+
+    pub fn is_currently_working(&self) -> bool {
+        if let Some(activity) = self.get_current_activity() {
+            activity.is_work
+        } else {
+            false
+        }
+    }
+
+    pub fn get_current_activity(&self) -> Option<&Activity> {
+        self.activities.last()
+    }
+
+    fn get_current_activity_mut(&mut self) -> Option<&mut Activity> {
+        self.activities.last_mut()
+    }
+
+    pub fn first_checkin_time(&self) -> Option<TimeStamp> {
+        self.activities.first().map(|activity| activity.time_start)
+    }
+
+    /// Returns the time of the final leave or `None` if the day is not over yet
+    pub fn last_checkout_time(&self) -> Option<TimeStamp> {
+        self.get_current_activity()
+            .filter(|activity| !activity.is_work && activity.time_end.is_none())
+            .map(|activity| activity.time_start)
+    }
+
+    pub fn get_time_left_for_the_day(
+        &self,
+        target_work_duration: TimeDuration,
+        mandatory_break_time: TimeDuration,
+    ) -> TimeDuration {
+        let mandatory_break_time_left = TimeDuration {
+            minutes: i32::max(
+                0,
+                (mandatory_break_time
+                    - self.get_break_duration()
+                    - self.get_leave_duration().unwrap_or(TimeDuration::zero()))
+                .minutes,
+            ),
+        };
+        target_work_duration - self.get_work_duration_total() + mandatory_break_time_left
+    }
+
+    pub fn get_work_duration_total(&self) -> TimeDuration {
+        self.activities
+            .iter()
+            .filter(|activity| activity.is_work)
+            .fold(TimeDuration::zero(), |acc, activity| {
+                acc + activity.duration(self.time_end_of_entry())
+            })
+    }
+
+    pub fn get_work_duration_specific(&self) -> TimeDuration {
+        self.activities
+            .iter()
+            .filter(|activity| activity.is_work)
+            .filter(|activity| activity.name != ACTIVITY_NAME_NON_SPECIFIC_WORK)
+            .fold(TimeDuration::zero(), |acc, activity| {
+                acc + activity.duration(self.time_end_of_entry())
+            })
+    }
+
+    pub fn get_work_duration_non_specific(&self) -> TimeDuration {
+        self.activities
+            .iter()
+            .filter(|activity| activity.is_work)
+            .filter(|activity| activity.name == ACTIVITY_NAME_NON_SPECIFIC_WORK)
+            .fold(TimeDuration::zero(), |acc, activity| {
+                acc + activity.duration(self.time_end_of_entry())
+            })
+    }
+
+    pub fn get_break_duration(&self) -> TimeDuration {
+        self.activities
+            .iter()
+            .filter(|activity| !activity.is_work)
+            .filter(|activity| activity.time_end.is_some())
+            .fold(TimeDuration::zero(), |acc, activity| {
+                acc + activity.duration(self.time_end_of_entry())
+            })
+    }
+
+    /// Returns the time since the last leave or `None` if currently working. Days in the past
+    /// have no time since their last leave.
+    pub fn get_leave_duration(&self) -> Option<TimeDuration> {
+        if !self.is_today() {
+            return None;
+        }
+        let current_activity = self.get_current_activity()?;
+        if current_activity.is_work {
+            None
+        } else {
+            Some(
+                self.activities
+                    .iter()
+                    .filter(|activity| !activity.is_work)
+                    .filter(|activity| activity.time_end.is_none())
+                    .fold(TimeDuration::zero(), |acc, activity| {
+                        acc + activity.duration(self.time_end_of_entry())
+                    }),
+            )
+        }
+    }
+
+    pub fn get_non_work_duration(&self) -> TimeDuration {
+        self.activities
+            .iter()
+            .filter(|activity| !activity.is_work)
+            .fold(TimeDuration::zero(), |acc, activity| {
+                acc + activity.duration(self.time_end_of_entry())
+            })
+    }
+
+    pub fn get_activity_durations(&self) -> IndexMap<String, TimeDuration> {
+        let activity_names: HashSet<String> = self
+            .activities
+            .iter()
+            .filter(|activity| activity.is_work)
+            .map(|activity| activity.name.clone())
+            .collect();
+
+        let mut activity_names_and_durations: IndexMap<String, TimeDuration> = activity_names
+            .into_iter()
+            .map(|activity_name| {
+                let duration = self
+                    .activities
+                    .iter()
+                    .filter(|activity| activity.name == activity_name)
+                    .fold(TimeDuration::zero(), |acc, activity| {
+                        acc + activity.duration(self.time_end_of_entry())
+                    });
+
+                (activity_name, duration)
+            })
+            .collect();
+
+        activity_names_and_durations.sort_by(
+            |_activity_name_a, duration_a, _activity_name_b, duration_b| {
+                // NOTE: The negatives forces descending sorting
+                (-duration_a.minutes).cmp(&-duration_b.minutes)
+            },
+        );
+
+        activity_names_and_durations
+    }
+
+    fn create_activities_from_stamp_events(
+        stamp_events: &[StampEvent],
+    ) -> Result<Vec<Activity>, String> {
+        let mut result = Vec::new();
+        let mut current_activity: Option<Activity> = None;
+        for event in stamp_events.iter() {
+            match event {
+                StampEvent::Begin(timestamp, activity_name) => {
+                    // Close current activity
+                    if current_activity.is_some() {
+                        if current_activity.as_ref().unwrap().name == *activity_name {
+                            return Err(format!(
+                                "Got a duplicate activity '{}' at {}",
+                                activity_name,
+                                timestamp.to_string()
+                            ));
+                        }
+                        current_activity.as_mut().unwrap().time_end = Some(*timestamp);
+                        result.push(current_activity.take().unwrap());
+                    }
+
+                    // Start new activity
+                    current_activity = Some(Activity {
+                        is_work: true,
+                        name: activity_name.to_owned(),
+                        time_start: *timestamp,
+                        time_end: None,
+                    });
+                }
+                StampEvent::Leave(timestamp) => {
+                    // Close current activity
+                    if current_activity.is_some() {
+                        if !current_activity.as_ref().unwrap().is_work {
+                            return Err(format!(
+                                "Got a duplicate leave activity at {}",
+                                timestamp.to_string()
+                            ));
+                        }
+                        current_activity.as_mut().unwrap().time_end = Some(*timestamp);
+                        result.push(current_activity.take().unwrap());
+                    }
+
+                    // Start new activity
+                    current_activity = Some(Activity {
+                        is_work: false,
+                        name: ACTIVITY_NAME_LEAVE.to_owned(),
+                        time_start: *timestamp,
+                        time_end: None,
+                    });
+                }
+            }
+        }
+        if let Some(current_activity) = current_activity {
+            result.push(current_activity);
+        }
+
+        DayEntry::cleanup_activities(&mut result);
+        Ok(result)
+    }
+
+    fn create_stamp_events_from_activities(activities: &[Activity]) -> Vec<StampEvent> {
+        let mut result = Vec::new();
+        for activity in activities.iter() {
+            if activity.is_work {
+                result.push(StampEvent::Begin(
+                    activity.time_start,
+                    activity.name.clone(),
+                ));
+            } else {
+                result.push(StampEvent::Leave(activity.time_start));
+            }
+        }
+        result
+    }
+
+    fn cleanup_activities(activities: &mut Vec<Activity>) {
+        // let mut debug = String::new();
+        // writeln!(debug, "before");
+        // for a in activities.iter() {
+        //     writeln!(debug, "{}", a.to_string());
+        // }
+
+        // Remove zero sized activities
+        activities.retain(|activity| activity.time_end != Some(activity.time_start));
+
+        // writeln!(debug, "after remove zeroes");
+        // for a in activities.iter() {
+        //     writeln!(debug, "{}", a.to_string());
+        // }
+        // std::fs::write("debug.txt", &debug);
+
+        // Rename all "leave" activities to "leave" so the next merge operation is easier
+        for activity in activities.iter_mut() {
+            if activity.is_work {
+                continue;
+            }
+            activity.name = ACTIVITY_NAME_LEAVE.to_owned();
+        }
+
+        // writeln!(debug, "after rename");
+        // for a in activities.iter() {
+        //     writeln!(debug, "{}", a.to_string());
+        // }
+        // std::fs::write("debug.txt", &debug);
+
+        // Merge adjacent same activities
+        // NOTE: These can occur after the previous removal operation
+        let mut result = Vec::new();
+        let mut current_activity: Option<Activity> = None;
+        for activity in activities.drain(..) {
+            if current_activity.is_none() {
+                current_activity = Some(activity);
+                continue;
+            }
+
+            // Merge if necessary
+            if let Some(current_activity) = current_activity.as_mut() {
+                if current_activity.is_work == activity.is_work
+                    && current_activity.name == activity.name
+                {
+                    current_activity.time_end = activity.time_end;
+                    continue;
+                }
+            }
+
+            result.push(current_activity.take().unwrap());
+            current_activity = Some(activity);
+        }
+        if let Some(current_activity) = current_activity {
+            result.push(current_activity);
+        }
+
+        *activities = result;
+
+        // writeln!(debug, "after merge");
+        // for a in activities.iter() {
+        //     writeln!(debug, "{}", a.to_string());
+        // }
+        // std::fs::write("debug.txt", &debug);
+
+        // Rename "leave" activities to "break" is the have a confirmed end
+        for activity in activities.iter_mut() {
+            if activity.is_work {
+                continue;
+            }
+            if activity.time_end.is_some() {
+                activity.name = ACTIVITY_NAME_BREAK.to_owned();
+            }
+        }
+
+        // writeln!(debug, "after rename");
+        // for a in activities.iter() {
+        //     writeln!(debug, "{}", a.to_string());
+        // }
+        // std::fs::write("debug.txt", &debug);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Activity {
+    pub is_work: bool,
+    pub name: String,
+    pub time_start: TimeStamp,
+    pub time_end: Option<TimeStamp>,
+}
+
+impl Activity {
+    pub fn to_string(&self, now: TimeStamp) -> String {
+        let time_range = if let Some(end) = self.time_end {
+            format!("{} - {}", self.time_start.to_string(), end.to_string())
+        } else {
+            format!("{} - {}", self.time_start.to_string(), "<now>")
+        };
+
+        format!(
+            "{} [{}] - [{}]",
+            time_range,
+            self.duration(now).to_string(),
+            self.name,
+        )
+    }
+
+    /// Returns the duration of the activity where an ongoing activity lasts until `now`
+    pub fn duration(&self, now: TimeStamp) -> TimeDuration {
+        if let Some(end) = self.time_end {
+            end - self.time_start
+        } else {
+            now - self.time_start
+        }
+    }
+}
+
+/// A single line of a timesheet
+#[derive(Debug, Clone)]
+pub enum StampEvent {
+    Begin(TimeStamp, String),
+    Leave(TimeStamp),
+}
+
+impl StampEvent {
+    pub fn timestamp(&self) -> TimeStamp {
+        match self {
+            StampEvent::Begin(timestamp, _name) => *timestamp,
+            StampEvent::Leave(timestamp) => *timestamp,
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        match self {
+            StampEvent::Begin(timestamp, name) => {
+                format!("{} - Begin [{}]", timestamp.to_string(), name)
+            }
+            StampEvent::Leave(timestamp) => format!("{} - Leave", timestamp.to_string()),
+        }
+    }
+
+    pub fn from_string(input: &str) -> Result<StampEvent, String> {
+        let re_begin = regex::Regex::new(r"(\d{2}:\d{2}) - Begin (\[.+\])").unwrap();
+        if let Some(capture) = re_begin.captures(input) {
+            let timestamp = TimeStamp::from_string(&capture[1])?;
+            let activity_name = capture[2]
+                .strip_prefix("[")
+                .unwrap()
+                .strip_suffix("]")
+                .unwrap()
+                .to_owned();
+            return Ok(StampEvent::Begin(timestamp, activity_name));
+        }
+
+        let re_leave = regex::Regex::new(r"(\d{2}:\d{2}) - Leave").unwrap();
+        if let Some(capture) = re_leave.captures(input) {
+            let timestamp = TimeStamp::from_string(&capture[1])?;
+            return Ok(StampEvent::Leave(timestamp));
+        }
+
+        Err(format!("The string '{}' is not a valid stamp event", input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+    use crate::storage::{MemoryStorage, Storage};
+
+    use std::sync::Arc;
+
+    /// Reports generated by the tests are compared against these files. Run the tests with
+    /// `THYME_UPDATE_GOLDEN=1` to regenerate them after an intended change of the reports.
+    const GOLDEN_DIRECTORY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd(2021, 3, 12)
+    }
+
+    fn stamp(text: &str) -> TimeStamp {
+        TimeStamp::from_string(text).unwrap()
+    }
+
+    /// Returns an empty day that starts at 08:00 together with the clock and storage it uses
+    fn create_day() -> (Arc<FakeClock>, Arc<MemoryStorage>, DayEntry) {
+        let clock = Arc::new(FakeClock::new(date().and_hms(8, 0, 0)));
+        let storage = MemoryStorage::shared();
+        let shared_clock: SharedClock = clock.clone();
+        let shared_storage: SharedStorage = storage.clone();
+        let day_entry = DayEntry::create_empty(&shared_clock, &shared_storage).unwrap();
+        (clock, storage, day_entry)
+    }
+
+    /// Starts the given activities one after another, each lasting the given minutes
+    fn stamp_sequence(clock: &FakeClock, day_entry: &mut DayEntry, sequence: &[(&str, bool, i64)]) {
+        for (name, is_work, duration) in sequence {
+            day_entry.start_activitiy(name, *is_work);
+            clock.advance(chrono::Duration::minutes(*duration));
+        }
+    }
+
+    fn check_golden(file_name: &str, actual: &str) {
+        let path = format!("{}/{}", GOLDEN_DIRECTORY, file_name);
+        if std::env::var("THYME_UPDATE_GOLDEN").is_ok() {
+            std::fs::write(&path, actual).unwrap();
+            return;
+        }
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|err| panic!("Could not read golden file '{}': {}", path, err));
+        assert_eq!(
+            expected, actual,
+            "Report differs from golden file '{}'",
+            path
+        );
+    }
+
+    #[test]
+    fn stamp_sequence_is_written_to_timesheet() {
+        let (clock, storage, mut day_entry) = create_day();
+        stamp_sequence(
+            &clock,
+            &mut day_entry,
+            &[
+                ("Meeting", true, 30),
+                ("Coding", true, 150),
+                (ACTIVITY_NAME_LEAVE, false, 45),
+                ("Coding", true, 90),
+            ],
+        );
+        day_entry.write_back().unwrap();
+
+        let expected = "Timesheet for 2021-03-12\n\
+                        ------------------------\n\
+                        \n\
+                        08:00 - Begin [Meeting]\n\
+                        08:30 - Begin [Coding]\n\
+                        11:00 - Leave\n\
+                        11:45 - Begin [Coding]\n";
+        assert_eq!(day_entry.to_timesheet_string(), expected);
+        assert_eq!(
+            storage.read_timesheet(Timesheet::Today).unwrap().as_deref(),
+            Some(expected)
+        );
+        assert_eq!(
+            storage
+                .read_timesheet(Timesheet::Day(date()))
+                .unwrap()
+                .as_deref(),
+            Some(expected)
+        );
+
+        let parsed =
+            DayEntry::from_timesheet_string(expected, &day_entry.clock, &day_entry.storage)
+                .unwrap();
+        assert_eq!(parsed.date, date());
+        assert_eq!(parsed.activities.len(), 4);
+        assert_eq!(parsed.activities[3].time_start, stamp("11:45"));
+        assert_eq!(parsed.activities[3].time_end, None);
+    }
+
+    #[test]
+    fn ongoing_activity_lasts_until_now() {
+        let (clock, _storage, mut day_entry) = create_day();
+        stamp_sequence(&clock, &mut day_entry, &[("Coding", true, 60)]);
+        assert_eq!(day_entry.get_work_duration_total().minutes, 60);
+
+        clock.advance(chrono::Duration::minutes(15));
+        assert_eq!(day_entry.time_now(), stamp("09:15"));
+        assert_eq!(day_entry.get_work_duration_total().minutes, 75);
+    }
+
+    #[test]
+    fn external_changes_of_today_are_reloaded() {
+        let (clock, storage, mut day_entry) = create_day();
+        stamp_sequence(&clock, &mut day_entry, &[("Coding", true, 60)]);
+        day_entry.write_back().unwrap();
+        assert!(!day_entry.hotreload_external_changes().unwrap());
+
+        let edited = day_entry
+            .to_timesheet_string()
+            .replace("08:00 - Begin [Coding]", "08:00 - Begin [Meeting]");
+        storage.write_timesheet(Timesheet::Today, &edited).unwrap();
+        assert!(day_entry.hotreload_external_changes().unwrap());
+        assert_eq!(day_entry.activities[0].name, "Meeting");
+
+        let reloaded = DayEntry::load_or_create(&day_entry.clock, &day_entry.storage).unwrap();
+        assert_eq!(reloaded.to_timesheet_string(), edited);
+    }
+
+    #[test]
+    fn ongoing_work_of_past_days_lasts_until_the_end_of_the_day() {
+        let (clock, _storage, mut day_entry) = create_day();
+        stamp_sequence(
+            &clock,
+            &mut day_entry,
+            &[("Coding", true, 60), (ACTIVITY_NAME_LEAVE, false, 60)],
+        );
+        clock.set(date().succ().and_hms(8, 30, 0));
+
+        // A past day that ends with leaving ends with its last stamp
+        assert_eq!(day_entry.time_end_of_entry(), stamp("09:00"));
+        assert_eq!(day_entry.get_work_duration_total().minutes, 60);
+        assert!(day_entry.get_leave_duration().is_none());
+
+        day_entry.activities.pop();
+        day_entry.activities[0].time_end = None;
+        assert_eq!(day_entry.time_end_of_entry(), TIME_END_OF_DAY);
+        assert_eq!(day_entry.get_work_duration_total().minutes, 15 * 60 + 59);
+        assert_eq!(
+            day_entry.activity_to_string(&day_entry.activities[0]),
+            "08:00 - 23:59 [15:59h] - [Coding]"
+        );
+    }
+
+    #[test]
+    fn leave_at_end_of_day_closes_ongoing_work() {
+        let (clock, _storage, mut day_entry) = create_day();
+        stamp_sequence(&clock, &mut day_entry, &[("Coding", true, 60)]);
+        clock.set(date().succ().and_hms(0, 0, 0));
+
+        day_entry.leave_at_end_of_day();
+        assert!(day_entry
+            .to_timesheet_string()
+            .ends_with("08:00 - Begin [Coding]\n23:59 - Leave\n"));
+        assert_eq!(day_entry.get_work_duration_total().minutes, 15 * 60 + 59);
+
+        // Nothing changes if the day already ended with leaving
+        let timesheet = day_entry.to_timesheet_string();
+        day_entry.leave_at_end_of_day();
+        assert_eq!(day_entry.to_timesheet_string(), timesheet);
+    }
+
+    #[test]
+    fn week_durations_before_are_reloaded_when_a_timesheet_changes() {
+        let (_clock, storage, day_entry) = create_day();
+        let write_thursday = |content: &str| {
+            storage
+                .write_timesheet(Timesheet::Day(date().pred()), content)
+                .unwrap()
+        };
+        let coding_minutes = |day_entry: &DayEntry| {
+            day_entry.load_week_durations_before().map(|durations| {
+                durations
+                    .get("Coding")
+                    .map(|duration| duration.minutes)
+                    .unwrap_or(0)
+            })
+        };
+
+        assert_eq!(coding_minutes(&day_entry), Ok(0));
+        write_thursday("Timesheet for 2021-03-11\n08:00 - Begin [Coding]\n09:00 - Leave\n");
+        assert_eq!(coding_minutes(&day_entry), Ok(60));
+        write_thursday("Timesheet for 2021-03-11\n08:00 - Begin [Coding]\n10:00 - Leave\n");
+        assert_eq!(coding_minutes(&day_entry.clone()), Ok(120));
+        write_thursday("Timesheet for 2021-03-11\n08:00 - Begin [Coding\n");
+        assert!(coding_minutes(&day_entry).is_err());
+    }
+
+    #[test]
+    fn broken_external_changes_of_today_keep_the_day_in_memory() {
+        let (clock, storage, mut day_entry) = create_day();
+        stamp_sequence(&clock, &mut day_entry, &[("Coding", true, 60)]);
+        day_entry.write_back().unwrap();
+        let timesheet = day_entry.to_timesheet_string();
+
+        storage
+            .write_timesheet(
+                Timesheet::Today,
+                "Timesheet for 2021-03-12\n08:00 - Begin [Coding\n",
+            )
+            .unwrap();
+        assert!(day_entry.hotreload_external_changes().is_err());
+        assert_eq!(day_entry.to_timesheet_string(), timesheet);
+    }
+
+    #[test]
+    fn cleanup_merges_adjacent_activities() {
+        let (clock, _storage, mut day_entry) = create_day();
+        // The zero length meeting in between is removed so that both coding stretches merge
+        stamp_sequence(
+            &clock,
+            &mut day_entry,
+            &[
+                ("Coding", true, 60),
+                ("Meeting", true, 0),
+                ("Coding", true, 30),
+            ],
+        );
+
+        assert_eq!(day_entry.activities.len(), 1);
+        assert_eq!(day_entry.activities[0].name, "Coding");
+        assert_eq!(day_entry.activities[0].time_start, stamp("08:00"));
+        assert_eq!(day_entry.activities[0].time_end, None);
+        assert_eq!(day_entry.get_work_duration_total().minutes, 90);
+    }
+
+    #[test]
+    fn cleanup_merges_adjacent_leaves_into_one_break() {
+        let activity = |name: &str, is_work: bool, start: &str, end: Option<&str>| Activity {
+            is_work,
+            name: name.to_owned(),
+            time_start: stamp(start),
+            time_end: end.map(stamp),
+        };
+        // A short lunch stamped without its own duration sits between two leaves
+        let mut activities = vec![
+            activity("Coding", true, "08:00", Some("12:00")),
+            activity(ACTIVITY_NAME_LEAVE, false, "12:00", Some("12:10")),
+            activity("Lunch", false, "12:10", Some("12:10")),
+            activity(ACTIVITY_NAME_LEAVE, false, "12:10", Some("13:00")),
+            activity("Coding", true, "13:00", None),
+        ];
+        DayEntry::cleanup_activities(&mut activities);
+
+        let summary: Vec<(String, TimeStamp, Option<TimeStamp>)> = activities
+            .iter()
+            .map(|activity| {
+                (
+                    activity.name.clone(),
+                    activity.time_start,
+                    activity.time_end,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Coding".to_owned(), stamp("08:00"), Some(stamp("12:00"))),
+                (
+                    ACTIVITY_NAME_BREAK.to_owned(),
+                    stamp("12:00"),
+                    Some(stamp("13:00"))
+                ),
+                ("Coding".to_owned(), stamp("13:00"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn leave_followed_by_work_counts_as_break() {
+        let (clock, _storage, mut day_entry) = create_day();
+        stamp_sequence(
+            &clock,
+            &mut day_entry,
+            &[
+                ("Coding", true, 240),
+                (ACTIVITY_NAME_LEAVE, false, 30),
+                ("Coding", true, 60),
+                (ACTIVITY_NAME_LEAVE, false, 20),
+            ],
+        );
+
+        // Only the finished leave counts as break, the ongoing one is the time since leaving
+        assert_eq!(day_entry.get_work_duration_total().minutes, 300);
+        assert_eq!(day_entry.get_break_duration().minutes, 30);
+        assert_eq!(day_entry.get_non_work_duration().minutes, 50);
+        assert_eq!(day_entry.get_leave_duration().map(|d| d.minutes), Some(20));
+        assert!(!day_entry.is_currently_working());
+
+        day_entry.start_activitiy("Coding", true);
+        assert_eq!(day_entry.get_break_duration().minutes, 50);
+        assert!(day_entry.get_leave_duration().is_none());
+        assert_eq!(day_entry.activities[3].name, ACTIVITY_NAME_BREAK);
+    }
+
+    #[test]
+    fn rename_activities_merges_and_reports_renamed_durations() {
+        let (clock, _storage, mut day_entry) = create_day();
+        stamp_sequence(
+            &clock,
+            &mut day_entry,
+            &[
+                ("Bugfixing", true, 60),
+                ("Coding", true, 30),
+                ("Debugging", true, 45),
+                (ACTIVITY_NAME_LEAVE, false, 15),
+            ],
+        );
+
+        let old_names = vec!["Bugfixing".to_owned(), "Debugging".to_owned()];
+        let renamed = day_entry.rename_activities(&old_names, " Coding ").unwrap();
+        let renamed: Vec<(String, i32)> = renamed
+            .into_iter()
+            .map(|(name, duration)| (name, duration.minutes))
+            .collect();
+        assert_eq!(
+            renamed,
+            vec![("Bugfixing".to_owned(), 60), ("Debugging".to_owned(), 45)]
+        );
+        assert_eq!(day_entry.activities.len(), 2);
+        assert_eq!(day_entry.activities[0].name, "Coding");
+        assert_eq!(day_entry.activities[0].time_end, Some(stamp("10:15")));
+
+        assert!(day_entry
+            .rename_activities(&old_names, "Coding")
+            .unwrap()
+            .is_empty());
+        assert!(day_entry.rename_activities(&old_names, "[Coding]").is_err());
+        assert!(day_entry.rename_activities(&old_names, " ").is_err());
+    }
+
+    #[test]
+    fn day_reports_match_golden_files() {
+        let (clock, storage, mut day_entry) = create_day();
+        stamp_sequence(
+            &clock,
+            &mut day_entry,
+            &[
+                ("Meeting", true, 45),
+                ("Coding", true, 135),
+                (ACTIVITY_NAME_NON_SPECIFIC_WORK, true, 60),
+                (ACTIVITY_NAME_LEAVE, false, 45),
+                ("Review", true, 90),
+                ("Coding", true, 150),
+                (ACTIVITY_NAME_LEAVE, false, 0),
+            ],
+        );
+
+        day_entry.write_back().unwrap();
+
+        let settings = ReportSettings::default();
+        day_entry.write_report(&settings, &[]).unwrap();
+        check_golden(
+            "day_report.txt",
+            &storage.report(Report::Day(date(), "txt")).unwrap(),
+        );
+        check_golden(
+            "day_report.html",
+            &storage.report(Report::Day(date(), "html")).unwrap(),
+        );
+
+        let settings = ReportSettings {
+            format: ReportFormat::Markdown,
+            ..ReportSettings::default()
+        };
+        day_entry.write_report(&settings, &[]).unwrap();
+        check_golden(
+            "day_report.md",
+            &storage.report(Report::Day(date(), "md")).unwrap(),
+        );
+        assert_eq!(storage.report(Report::Day(date(), "txt")), None);
+    }
+}
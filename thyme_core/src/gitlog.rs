@@ -0,0 +1,285 @@
+use crate::dayentry::{Activity, DayEntry, ACTIVITY_NAME_NON_SPECIFIC_WORK};
+use crate::time::{DateTimeHelper, TimeStamp};
+
+use chrono::prelude::*;
+
+use std::{cell::RefCell, collections::HashMap, process::Command};
+
+/// Running git for every repository on every report is slow. Commits of a day that were looked
+/// up after the day ended do not change anymore, commits of the current day are looked up again
+/// after this many minutes.
+const COMMITS_REFRESH_INTERVAL_MINUTES: i64 = 5;
+
+/// Repository path, author and date
+type CommitsCacheKey = (String, Option<String>, NaiveDate);
+
+struct CachedCommits {
+    lookup_time: NaiveDateTime,
+    commits: Result<Vec<GitCommit>, String>,
+}
+
+thread_local! {
+    static COMMITS_CACHE: RefCell<HashMap<CommitsCacheKey, CachedCommits>> =
+        RefCell::new(HashMap::new());
+}
+
+/// A local git repository given via `git_repository = <path> | activity: <name>` in the config
+#[derive(Debug, Clone)]
+pub struct GitRepository {
+    pub path: String,
+    /// The activity that is suggested for non-specific work during which commits were made in
+    /// this repository. Defaults to the name of the repository directory.
+    pub activity_name: Option<String>,
+}
+
+impl GitRepository {
+    pub fn from_string(input: &str) -> Option<GitRepository> {
+        let mut parts = input.split('|').map(|part| part.trim());
+        let path = parts.next().filter(|path| !path.is_empty())?;
+        let mut activity_name = None;
+        for attribute in parts {
+            let (key, value) = attribute.split_once(':')?;
+            match key.trim() {
+                "activity" => activity_name = Some(value.trim().to_owned()),
+                _ => return None,
+            }
+        }
+
+        let path = match (path.strip_prefix("~/"), std::env::var("HOME")) {
+            (Some(path_in_home), Ok(home)) => format!("{}/{}", home, path_in_home),
+            _ => path.to_owned(),
+        };
+        Some(GitRepository {
+            path,
+            activity_name,
+        })
+    }
+
+    pub fn name(&self) -> String {
+        std::path::Path::new(&self.path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.clone())
+    }
+
+    pub fn suggested_activity_name(&self) -> String {
+        self.activity_name.clone().unwrap_or_else(|| self.name())
+    }
+
+    fn run_git(&self, arguments: &[&str]) -> Result<String, String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.path)
+            .args(arguments)
+            .output()
+            .map_err(|error| format!("Could not run git for '{}' - {}", self.path, error))?;
+        if !output.status.success() {
+            return Err(format!(
+                "git failed for '{}' - {}",
+                self.path,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Same as `commits_for_date` but only runs git again if the commits of the date may have
+    /// changed since they were looked up last
+    fn cached_commits_for_date(
+        &self,
+        author: Option<&str>,
+        date: NaiveDate,
+        now: NaiveDateTime,
+    ) -> Result<Vec<GitCommit>, String> {
+        let key = (
+            self.path.clone(),
+            author.map(|author| author.to_owned()),
+            date,
+        );
+        COMMITS_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if let Some(cached) = cache.get(&key) {
+                let is_final = cached.commits.is_ok() && cached.lookup_time.date() > date;
+                let is_recent =
+                    (now - cached.lookup_time).num_minutes() < COMMITS_REFRESH_INTERVAL_MINUTES;
+                if is_final || is_recent {
+                    return cached.commits.clone();
+                }
+            }
+
+            let commits = self.commits_for_date(author, date);
+            cache.insert(
+                key,
+                CachedCommits {
+                    lookup_time: now,
+                    commits: commits.clone(),
+                },
+            );
+            commits
+        })
+    }
+
+    /// Returns all commits of the given author (or the `user.email` configured for the
+    /// repository if none is given) that were authored on the given date
+    fn commits_for_date(
+        &self,
+        author: Option<&str>,
+        date: NaiveDate,
+    ) -> Result<Vec<GitCommit>, String> {
+        let author = match author {
+            Some(author) => Some(author.to_owned()),
+            None => self
+                .run_git(&["config", "user.email"])
+                .ok()
+                .map(|email| email.trim().to_owned())
+                .filter(|email| !email.is_empty()),
+        };
+
+        // NOTE: `--since` and `--until` filter by committer date which can differ from the
+        //       author date after rebasing. We therefore query a wider range and filter by the
+        //       author date afterwards.
+        let since = format!("--since={} 00:00", date.pred().format("%Y-%m-%d"));
+        let until = format!("--until={} 23:59", date.succ().format("%Y-%m-%d"));
+        let mut arguments = vec![
+            "log",
+            "--all",
+            "--no-merges",
+            "--date=format-local:%Y-%m-%d %H:%M",
+            "--format=%h%x09%ad%x09%s",
+            &since,
+            &until,
+        ];
+        let author_argument = author.map(|author| format!("--author={}", author));
+        if let Some(author_argument) = &author_argument {
+            arguments.push(author_argument);
+        }
+
+        let mut result: Vec<GitCommit> = self
+            .run_git(&arguments)?
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '\t');
+                let hash = parts.next()?;
+                let datetime =
+                    NaiveDateTime::parse_from_str(parts.next()?, "%Y-%m-%d %H:%M").ok()?;
+                let summary = parts.next().unwrap_or("");
+                Some(GitCommit {
+                    repository_name: self.name(),
+                    suggested_activity_name: self.suggested_activity_name(),
+                    hash: hash.to_owned(),
+                    datetime,
+                    summary: summary.to_owned(),
+                })
+            })
+            .filter(|commit| commit.datetime.date() == date)
+            .collect();
+        result.sort_by_key(|commit| commit.datetime);
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GitCommit {
+    pub repository_name: String,
+    pub suggested_activity_name: String,
+    pub hash: String,
+    pub datetime: NaiveDateTime,
+    pub summary: String,
+}
+
+impl GitCommit {
+    pub fn to_string(&self) -> String {
+        format!(
+            "{} {} {} {}",
+            self.datetime.to_timestamp().to_string(),
+            self.repository_name,
+            self.hash,
+            self.summary
+        )
+    }
+}
+
+/// The commits that were made during the activities of a day
+pub struct GitActivityLog {
+    /// The commits for every activity of the day in the same order as `DayEntry::activities`
+    pub commits_per_activity: Vec<Vec<GitCommit>>,
+    /// Repositories that could not be read
+    pub errors: Vec<String>,
+}
+
+impl GitActivityLog {
+    pub fn collect(
+        day_entry: &DayEntry,
+        repositories: &[GitRepository],
+        author: Option<&str>,
+    ) -> GitActivityLog {
+        let mut commits = Vec::new();
+        let mut errors = Vec::new();
+        for repository in repositories {
+            match repository.cached_commits_for_date(author, day_entry.date, day_entry.clock.now())
+            {
+                Ok(repository_commits) => commits.extend(repository_commits),
+                Err(error) => errors.push(error),
+            }
+        }
+        commits.sort_by_key(|commit| commit.datetime);
+
+        let commits_per_activity = day_entry
+            .activities
+            .iter()
+            .map(|activity| {
+                commits
+                    .iter()
+                    .filter(|commit| is_during_activity(commit.datetime.to_timestamp(), activity))
+                    .cloned()
+                    .collect()
+            })
+            .collect();
+
+        GitActivityLog {
+            commits_per_activity,
+            errors,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+            && self
+                .commits_per_activity
+                .iter()
+                .all(|commits| commits.is_empty())
+    }
+
+    /// Suggests an activity name for the given non-specific work activity based on the
+    /// repository with the most commits during it. Returns the name and number of commits.
+    pub fn suggest_activity_name(
+        &self,
+        activity_index: usize,
+        activity: &Activity,
+    ) -> Option<(String, usize)> {
+        if activity.name != ACTIVITY_NAME_NON_SPECIFIC_WORK {
+            return None;
+        }
+
+        let mut commit_counts: Vec<(String, usize)> = Vec::new();
+        for commit in &self.commits_per_activity[activity_index] {
+            match commit_counts
+                .iter_mut()
+                .find(|(name, _count)| *name == commit.suggested_activity_name)
+            {
+                Some((_name, count)) => *count += 1,
+                None => commit_counts.push((commit.suggested_activity_name.clone(), 1)),
+            }
+        }
+        // NOTE: On ties the repository whose commit came first wins
+        commit_counts
+            .into_iter()
+            .rev()
+            .max_by_key(|(_name, count)| *count)
+    }
+}
+
+fn is_during_activity(timestamp: TimeStamp, activity: &Activity) -> bool {
+    // NOTE: A commit made exactly at the switch between two activities belongs to the second
+    activity.time_start <= timestamp && activity.time_end.map(|end| timestamp < end).unwrap_or(true)
+}
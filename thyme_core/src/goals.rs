@@ -0,0 +1,62 @@
+use crate::budget::PeriodDuration;
+use crate::time::TimeDuration;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GoalKind {
+    /// The activity should be done for at least the target duration
+    AtLeast,
+    /// The activity should be done for at most the target duration
+    AtMost,
+}
+
+impl GoalKind {
+    pub fn to_string(self) -> &'static str {
+        match self {
+            GoalKind::AtLeast => "min",
+            GoalKind::AtMost => "max",
+        }
+    }
+}
+
+/// A goal for an activity given as `min: 01:00/day` or `max: 05:00/week` in the activity list
+#[derive(Debug, Copy, Clone)]
+pub struct Goal {
+    pub kind: GoalKind,
+    pub target: PeriodDuration,
+}
+
+impl Goal {
+    pub fn to_string(self) -> String {
+        format!("{} {}", self.kind.to_string(), self.target.to_string())
+    }
+
+    /// Returns whether the goal is reached with the given tracked duration. Note that a maximum
+    /// goal is already reached without tracking anything.
+    pub fn is_reached(&self, tracked: TimeDuration) -> bool {
+        match self.kind {
+            GoalKind::AtLeast => tracked.minutes >= self.target.duration.minutes,
+            GoalKind::AtMost => tracked.minutes <= self.target.duration.minutes,
+        }
+    }
+
+    /// Returns the tracked duration relative to the target duration, can be greater than one
+    pub fn progress(&self, tracked: TimeDuration) -> f32 {
+        if self.target.duration.minutes <= 0 {
+            return if tracked.minutes > 0 { f32::MAX } else { 1.0 };
+        }
+        tracked.minutes as f32 / self.target.duration.minutes as f32
+    }
+
+    /// Returns a progress bar like `█████░░░░░` with the given width
+    pub fn progress_bar(&self, tracked: TimeDuration, width: usize) -> String {
+        let filled_count = usize::min(
+            width,
+            (f32::min(self.progress(tracked), 1.0) * width as f32).round() as usize,
+        );
+        format!(
+            "{}{}",
+            "█".repeat(filled_count),
+            "░".repeat(width - filled_count)
+        )
+    }
+}
@@ -0,0 +1,38 @@
+//! Core of the Thyme timetracker: reading and writing timesheets, the activity list and
+//! generating reports and invoices. The `launcher` binary is a terminal user interface on top of
+//! this library.
+//!
+//! All functions that read or write data or parse user provided input return a `Result` with a
+//! human readable error message. Timesheets and reports are read from and written to a `Storage`
+//! which is either the text files in a directory, memory or (with the `sqlite` feature) a SQLite
+//! database.
+
+// NOTE: Types are formatted by inherent `to_string` methods next to their `from_string`
+//       counterparts instead of `Display` implementations
+#![allow(clippy::inherent_to_string)]
+
+pub mod activitylist;
+pub mod billing;
+pub mod budget;
+pub mod check;
+pub mod clock;
+pub mod dayentry;
+pub mod gitlog;
+pub mod goals;
+pub mod query;
+pub mod report_html;
+pub mod report_markdown;
+pub mod rounding;
+pub mod statistics;
+pub mod storage;
+#[cfg(feature = "sqlite")]
+pub mod storage_sqlite;
+pub mod time;
+
+pub use activitylist::ActivityListEntry;
+pub use clock::{Clock, FakeClock, RealClock, SharedClock};
+pub use dayentry::{Activity, DayEntry, ReportFormat, ReportSettings, StampEvent};
+pub use storage::{MemoryStorage, SharedStorage, Storage, TextFileStorage};
+pub use time::{TimeDuration, TimeStamp};
+
+pub use ct_lib_core::indexmap;
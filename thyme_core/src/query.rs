@@ -0,0 +1,778 @@
+use crate::activitylist::ActivityListEntry;
+use crate::dayentry::{Activity, DayEntry};
+use crate::report_markdown::escape_markdown_table_cell;
+use crate::time::TimeDuration;
+
+use chrono::prelude::*;
+use regex::Regex;
+
+use std::{collections::HashMap, fmt::Write};
+
+/// Short description of the query terms, i.e. for the command line usage
+pub const QUERY_TERMS_HELP: &str = "\
+date:<period>[..<period>]  Only days in the period. A period is a date (YYYY-MM-DD), a month
+                           (YYYY-MM), a year (YYYY), 'today', 'yesterday' or the current
+                           'week', 'month' or 'year'. Default: all days in the database
+weekday:<day>[,<day>...]   Only the given weekdays (i.e. 'mon,tue' or 'friday')
+name:<pattern>             Activity names matching the pattern where '*' matches anything
+                           (case-insensitive) or a regular expression written as '/regex/'
+tag:<tag>                  Activities with the given tag in the activity list
+client:<client>            Activities of the given client in the activity list
+work:yes|no|any            Work activities (default), breaks or both
+by:<group>[,<group>...]    Groups the results by 'activity', 'tag', 'client', 'day', 'week',
+                           'month', 'year' or 'weekday'. Default: only the total
+A '-' in front of a 'weekday', 'name', 'tag' or 'client' term excludes matching activities.";
+
+enum FilterKind {
+    Weekdays(Vec<Weekday>),
+    Name(Box<Regex>),
+    Tag(String),
+    Client(String),
+}
+
+struct Filter {
+    kind: FilterKind,
+    is_negated: bool,
+}
+
+impl Filter {
+    fn matches(
+        &self,
+        day_entry: &DayEntry,
+        activity: &Activity,
+        list_entry: Option<&ActivityListEntry>,
+    ) -> bool {
+        let is_match = match &self.kind {
+            FilterKind::Weekdays(weekdays) => weekdays.contains(&day_entry.date.weekday()),
+            FilterKind::Name(regex) => regex.is_match(&activity.name),
+            FilterKind::Tag(tag) => list_entry
+                .map(|entry| {
+                    entry
+                        .tags
+                        .iter()
+                        .any(|other| other.eq_ignore_ascii_case(tag))
+                })
+                .unwrap_or(false),
+            FilterKind::Client(client) => list_entry
+                .and_then(|entry| entry.client.as_ref())
+                .map(|other| other.eq_ignore_ascii_case(client))
+                .unwrap_or(false),
+        };
+        is_match != self.is_negated
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Grouping {
+    Activity,
+    Tag,
+    Client,
+    Day,
+    Week,
+    Month,
+    Year,
+    Weekday,
+}
+
+impl Grouping {
+    fn from_string(input: &str) -> Option<Grouping> {
+        match input {
+            "activity" => Some(Grouping::Activity),
+            "tag" => Some(Grouping::Tag),
+            "client" => Some(Grouping::Client),
+            "day" => Some(Grouping::Day),
+            "week" => Some(Grouping::Week),
+            "month" => Some(Grouping::Month),
+            "year" => Some(Grouping::Year),
+            "weekday" => Some(Grouping::Weekday),
+            _ => None,
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Grouping::Activity => "Activity",
+            Grouping::Tag => "Tag",
+            Grouping::Client => "Client",
+            Grouping::Day => "Day",
+            Grouping::Week => "Week",
+            Grouping::Month => "Month",
+            Grouping::Year => "Year",
+            Grouping::Weekday => "Weekday",
+        }
+    }
+
+    fn is_chronological(&self) -> bool {
+        !matches!(self, Grouping::Activity | Grouping::Tag | Grouping::Client)
+    }
+
+    /// Returns the groups the activity belongs to. Activities with multiple tags belong to
+    /// multiple groups.
+    fn groups(
+        &self,
+        day_entry: &DayEntry,
+        activity: &Activity,
+        list_entry: Option<&ActivityListEntry>,
+    ) -> Vec<Group> {
+        let date = day_entry.date;
+        match self {
+            Grouping::Activity => vec![Group::named(&activity.name)],
+            Grouping::Tag => {
+                let tags = list_entry.map(|entry| entry.tags.as_slice()).unwrap_or(&[]);
+                if tags.is_empty() {
+                    vec![Group::named("(no tag)")]
+                } else {
+                    tags.iter().map(|tag| Group::named(tag)).collect()
+                }
+            }
+            Grouping::Client => vec![Group::named(
+                list_entry
+                    .and_then(|entry| entry.client.as_deref())
+                    .unwrap_or("(no client)"),
+            )],
+            Grouping::Day => vec![Group {
+                order: date.num_days_from_ce() as i64,
+                label: date.format("%Y-%m-%d %a").to_string(),
+            }],
+            Grouping::Week => {
+                let week = date.iso_week();
+                vec![Group {
+                    order: week.year() as i64 * 100 + week.week() as i64,
+                    label: format!("{}-W{:02}", week.year(), week.week()),
+                }]
+            }
+            Grouping::Month => vec![Group {
+                order: date.year() as i64 * 100 + date.month() as i64,
+                label: date.format("%Y-%m").to_string(),
+            }],
+            Grouping::Year => vec![Group {
+                order: date.year() as i64,
+                label: date.year().to_string(),
+            }],
+            Grouping::Weekday => vec![Group {
+                order: date.weekday().num_days_from_monday() as i64,
+                label: date.format("%a").to_string(),
+            }],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Group {
+    /// Sort order of chronological groups
+    order: i64,
+    label: String,
+}
+
+impl Group {
+    fn named(label: &str) -> Group {
+        Group {
+            order: 0,
+            label: label.to_owned(),
+        }
+    }
+}
+
+/// Selects activities from a range of days and sums up their durations, optionally grouped.
+/// A query is given as a list of terms like `date:2021-03 name:Bugfixing -weekday:fri by:week`
+/// (see `QUERY_TERMS_HELP`). All terms must match for an activity to be counted.
+pub struct Query {
+    pub date_from: Option<NaiveDate>,
+    pub date_to: Option<NaiveDate>,
+    filters: Vec<Filter>,
+    /// Only work activities, only breaks or both if `None`
+    is_work: Option<bool>,
+    groupings: Vec<Grouping>,
+    text: String,
+}
+
+impl Query {
+    pub fn parse(terms: &[String], today: NaiveDate) -> Result<Query, String> {
+        let mut result = Query {
+            date_from: None,
+            date_to: None,
+            filters: Vec::new(),
+            is_work: Some(true),
+            groupings: Vec::new(),
+            text: terms.join(" "),
+        };
+
+        for term in terms {
+            let (is_negated, body) = match term.strip_prefix('-') {
+                Some(body) => (true, body),
+                None => (false, term.as_str()),
+            };
+            let (key, value) = body
+                .split_once(':')
+                .filter(|(_, value)| !value.is_empty())
+                .ok_or_else(|| format!("Query term '{}' is not of the form 'key:value'", term))?;
+            if is_negated && !matches!(key, "weekday" | "name" | "tag" | "client") {
+                return Err(format!("Query term '{}' cannot be negated", term));
+            }
+
+            let kind = match key {
+                "date" => {
+                    if result.date_from.is_some() {
+                        return Err(format!("Query term '{}' - only one date range is allowed", term));
+                    }
+                    let (date_from, date_to) = parse_date_range(value, today)?;
+                    result.date_from = Some(date_from);
+                    result.date_to = Some(date_to);
+                    continue;
+                }
+                "work" => {
+                    result.is_work = match value {
+                        "yes" => Some(true),
+                        "no" => Some(false),
+                        "any" => None,
+                        _ => {
+                            return Err(format!(
+                                "Query term '{}' - expected 'work:yes', 'work:no' or 'work:any'",
+                                term
+                            ))
+                        }
+                    };
+                    continue;
+                }
+                "by" => {
+                    for name in value.split(',') {
+                        let grouping = Grouping::from_string(name.trim()).ok_or_else(|| {
+                            format!("Query term '{}' - cannot group by '{}'", term, name)
+                        })?;
+                        result.groupings.push(grouping);
+                    }
+                    continue;
+                }
+                "weekday" => FilterKind::Weekdays(
+                    value
+                        .split(',')
+                        .map(|name| {
+                            name.trim().parse::<Weekday>().map_err(|_| {
+                                format!("Query term '{}' - '{}' is not a weekday", term, name)
+                            })
+                        })
+                        .collect::<Result<_, _>>()?,
+                ),
+                "name" => FilterKind::Name(Box::new(parse_name_pattern(value).map_err(
+                    |error| format!("Query term '{}' - invalid pattern: {}", term, error),
+                )?)),
+                "tag" => FilterKind::Tag(value.to_owned()),
+                "client" => FilterKind::Client(value.to_owned()),
+                _ => {
+                    return Err(format!(
+                        "Unknown query term '{}' - known terms are 'date', 'weekday', 'name', 'tag', 'client', 'work' and 'by'",
+                        term
+                    ))
+                }
+            };
+            result.filters.push(Filter { kind, is_negated });
+        }
+        Ok(result)
+    }
+
+    fn matches(
+        &self,
+        day_entry: &DayEntry,
+        activity: &Activity,
+        list_entry: Option<&ActivityListEntry>,
+    ) -> bool {
+        let is_before_range = matches!(self.date_from, Some(from) if day_entry.date < from);
+        let is_after_range = matches!(self.date_to, Some(to) if to < day_entry.date);
+        let is_excluded_work = matches!(self.is_work, Some(is_work) if activity.is_work != is_work);
+        !is_before_range
+            && !is_after_range
+            && !is_excluded_work
+            && self
+                .filters
+                .iter()
+                .all(|filter| filter.matches(day_entry, activity, list_entry))
+    }
+
+    pub fn run(
+        &self,
+        day_entries: &[DayEntry],
+        activity_list: &[ActivityListEntry],
+    ) -> QueryResult {
+        let mut result = QueryResult {
+            text: self.text.clone(),
+            groupings: self.groupings.clone(),
+            rows: Vec::new(),
+            total: TimeDuration::zero(),
+            matching_dates: Vec::new(),
+        };
+
+        let mut durations: HashMap<Vec<Group>, TimeDuration> = HashMap::new();
+        for day_entry in day_entries {
+            let now = day_entry.time_end_of_entry();
+            for activity in &day_entry.activities {
+                // NOTE: The time after leaving at the end of a day is no break
+                if !activity.is_work && activity.time_end.is_none() {
+                    continue;
+                }
+                let list_entry = activity_list
+                    .iter()
+                    .find(|entry| entry.name == activity.name);
+                if !self.matches(day_entry, activity, list_entry) {
+                    continue;
+                }
+
+                let duration = activity.duration(now);
+                result.total += duration;
+                if result.matching_dates.last() != Some(&day_entry.date) {
+                    result.matching_dates.push(day_entry.date);
+                }
+
+                let mut keys: Vec<Vec<Group>> = vec![Vec::new()];
+                for grouping in &self.groupings {
+                    let groups = grouping.groups(day_entry, activity, list_entry);
+                    keys = keys
+                        .iter()
+                        .flat_map(|key| {
+                            groups.iter().map(move |group| {
+                                let mut key = key.clone();
+                                key.push(group.clone());
+                                key
+                            })
+                        })
+                        .collect();
+                }
+                for key in keys {
+                    *durations.entry(key).or_insert_with(TimeDuration::zero) += duration;
+                }
+            }
+        }
+
+        let mut rows: Vec<(Vec<Group>, TimeDuration)> = durations.into_iter().collect();
+        if self
+            .groupings
+            .iter()
+            .any(|grouping| grouping.is_chronological())
+        {
+            rows.sort_by(|(a, _), (b, _)| {
+                a.iter()
+                    .zip(b.iter())
+                    .map(|(a, b)| a.order.cmp(&b.order).then_with(|| a.label.cmp(&b.label)))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+        } else {
+            rows.sort_by(|(a_key, a), (b_key, b)| {
+                b.minutes.cmp(&a.minutes).then_with(|| {
+                    a_key
+                        .iter()
+                        .map(|group| &group.label)
+                        .cmp(b_key.iter().map(|group| &group.label))
+                })
+            });
+        }
+        result.rows = rows
+            .into_iter()
+            .map(|(key, duration)| (key.into_iter().map(|group| group.label).collect(), duration))
+            .collect();
+        result
+    }
+}
+
+/// The total duration of all matching activities and the durations per group
+pub struct QueryResult {
+    text: String,
+    groupings: Vec<Grouping>,
+    pub rows: Vec<(Vec<String>, TimeDuration)>,
+    pub total: TimeDuration,
+    /// All days with at least one matching activity in ascending order
+    pub matching_dates: Vec<NaiveDate>,
+}
+
+impl QueryResult {
+    fn share_percent(&self, duration: TimeDuration) -> i32 {
+        if self.total.minutes > 0 {
+            (100.0 * duration.minutes as f32 / self.total.minutes as f32).round() as i32
+        } else {
+            0
+        }
+    }
+
+    fn days_summary(&self) -> String {
+        match (self.matching_dates.first(), self.matching_dates.last()) {
+            (Some(first), Some(last)) => format!(
+                "{} days with matching activities between {} and {}",
+                self.matching_dates.len(),
+                first.format("%Y-%m-%d"),
+                last.format("%Y-%m-%d")
+            ),
+            _ => "No matching activities".to_owned(),
+        }
+    }
+
+    fn tag_note(&self) -> Option<&'static str> {
+        if self.groupings.contains(&Grouping::Tag) {
+            Some("Activities with multiple tags are counted once for every tag")
+        } else {
+            None
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut result = String::new();
+        writeln!(result, "Query: {}", self.text).unwrap();
+        writeln!(result, "{}\n", self.days_summary()).unwrap();
+
+        if !self.groupings.is_empty() {
+            let mut widths: Vec<usize> = self
+                .groupings
+                .iter()
+                .map(|grouping| grouping.title().chars().count())
+                .collect();
+            for (labels, _) in &self.rows {
+                for (width, label) in widths.iter_mut().zip(labels) {
+                    *width = (*width).max(label.chars().count());
+                }
+            }
+            let duration_width = self
+                .rows
+                .iter()
+                .map(|(_, duration)| duration.to_string().len())
+                .chain(std::iter::once(self.total.to_string().len()))
+                .max()
+                .unwrap_or(0)
+                .max("Duration".len());
+
+            let mut header = String::new();
+            for (grouping, width) in self.groupings.iter().zip(&widths) {
+                write!(header, "{:<width$}  ", grouping.title(), width = width).unwrap();
+            }
+            write!(
+                header,
+                "{:>width$}  Share",
+                "Duration",
+                width = duration_width
+            )
+            .unwrap();
+            let separator = "-".repeat(header.chars().count());
+            writeln!(result, "{}\n{}", header, separator).unwrap();
+
+            for (labels, duration) in &self.rows {
+                for (label, width) in labels.iter().zip(&widths) {
+                    write!(result, "{:<width$}  ", label, width = width).unwrap();
+                }
+                writeln!(
+                    result,
+                    "{:>width$}  {:>4}%",
+                    duration.to_string(),
+                    self.share_percent(*duration),
+                    width = duration_width
+                )
+                .unwrap();
+            }
+            writeln!(result, "{}", separator).unwrap();
+            let label_width: usize = widths.iter().map(|width| width + 2).sum();
+            writeln!(
+                result,
+                "{:<label_width$}{:>duration_width$}",
+                "Total",
+                self.total.to_string(),
+                label_width = label_width,
+                duration_width = duration_width
+            )
+            .unwrap();
+            if let Some(note) = self.tag_note() {
+                writeln!(result, "\n{}", note).unwrap();
+            }
+        } else {
+            writeln!(result, "Total: {}", self.total.to_string()).unwrap();
+        }
+        result
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut result = String::new();
+        writeln!(result, "# Query `{}`\n", self.text).unwrap();
+        writeln!(result, "{}\n", self.days_summary()).unwrap();
+
+        if !self.groupings.is_empty() {
+            for grouping in &self.groupings {
+                write!(result, "| {} ", grouping.title()).unwrap();
+            }
+            writeln!(result, "| Duration | Share |").unwrap();
+            writeln!(result, "{}|---:|---:|", "|---".repeat(self.groupings.len())).unwrap();
+            for (labels, duration) in &self.rows {
+                for label in labels {
+                    write!(result, "| {} ", escape_markdown_table_cell(label)).unwrap();
+                }
+                writeln!(
+                    result,
+                    "| {} | {}% |",
+                    duration.to_string(),
+                    self.share_percent(*duration)
+                )
+                .unwrap();
+            }
+            write!(result, "| **Total** ").unwrap();
+            for _ in 1..self.groupings.len() {
+                write!(result, "| ").unwrap();
+            }
+            writeln!(result, "| **{}** | |", self.total.to_string()).unwrap();
+            if let Some(note) = self.tag_note() {
+                writeln!(result, "\n{}", note).unwrap();
+            }
+        } else {
+            writeln!(result, "**Total: {}**", self.total.to_string()).unwrap();
+        }
+        result
+    }
+}
+
+/// Patterns are either regular expressions like `/^Bug(fix)?/` or case-insensitive names where
+/// `*` matches anything
+fn parse_name_pattern(pattern: &str) -> Result<Regex, regex::Error> {
+    if let Some(regex) = pattern
+        .strip_prefix('/')
+        .and_then(|pattern| pattern.strip_suffix('/'))
+    {
+        Regex::new(regex)
+    } else {
+        let parts: Vec<String> = pattern.split('*').map(regex::escape).collect();
+        Regex::new(&format!("(?i)^{}$", parts.join(".*")))
+    }
+}
+
+fn parse_date_range(input: &str, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), String> {
+    let (date_from, date_to) = match input.split_once("..") {
+        Some((from, to)) => (parse_period(from, today)?.0, parse_period(to, today)?.1),
+        None => parse_period(input, today)?,
+    };
+    if date_to < date_from {
+        return Err(format!("The date range '{}' ends before it starts", input));
+    }
+    Ok((date_from, date_to))
+}
+
+/// Returns the first and last day of the given day, month or year
+fn parse_period(input: &str, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), String> {
+    let month_range = |year: i32, month: u32| -> Option<(NaiveDate, NaiveDate)> {
+        let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let next = if month == 12 {
+            NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+        } else {
+            NaiveDate::from_ymd_opt(year, month + 1, 1)?
+        };
+        Some((first, next.pred()))
+    };
+    let year_range = |year: i32| -> Option<(NaiveDate, NaiveDate)> {
+        Some((
+            NaiveDate::from_ymd_opt(year, 1, 1)?,
+            NaiveDate::from_ymd_opt(year, 12, 31)?,
+        ))
+    };
+
+    let range = match input {
+        "today" => Some((today, today)),
+        "yesterday" => Some((today.pred(), today.pred())),
+        "week" => {
+            let monday =
+                today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64);
+            Some((monday, monday + chrono::Duration::days(6)))
+        }
+        "month" => month_range(today.year(), today.month()),
+        "year" => year_range(today.year()),
+        _ => {
+            let numbers: Vec<&str> = input.split('-').collect();
+            match numbers.as_slice() {
+                [_, _, _] => NaiveDate::parse_from_str(input, "%Y-%m-%d")
+                    .ok()
+                    .map(|date| (date, date)),
+                [year, month] if year.len() == 4 => match (year.parse(), month.parse()) {
+                    (Ok(year), Ok(month)) => month_range(year, month),
+                    _ => None,
+                },
+                [year] if year.len() == 4 => year.parse().ok().and_then(year_range),
+                _ => None,
+            }
+        }
+    };
+    range.ok_or_else(|| {
+        format!(
+            "'{}' is not a valid date (YYYY-MM-DD), month (YYYY-MM) or year (YYYY)",
+            input
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FakeClock, SharedClock};
+    use crate::storage::{MemoryStorage, SharedStorage};
+
+    use std::sync::Arc;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd(2021, 3, day)
+    }
+
+    fn terms(input: &str) -> Vec<String> {
+        input.split_whitespace().map(str::to_owned).collect()
+    }
+
+    fn day_entries() -> Vec<DayEntry> {
+        let clock: SharedClock = Arc::new(FakeClock::new(date(20).and_hms(12, 0, 0)));
+        let storage: SharedStorage = MemoryStorage::shared();
+        [
+            "Timesheet for 2021-03-11\n\
+             08:00 - Begin [Coding]\n\
+             10:00 - Begin [Meeting]\n\
+             11:00 - Leave\n\
+             12:00 - Begin [Bugfixing]\n\
+             14:00 - Leave\n",
+            "Timesheet for 2021-03-12\n\
+             09:00 - Begin [Coding]\n\
+             12:00 - Leave\n",
+        ]
+        .iter()
+        .map(|timesheet| DayEntry::from_timesheet_string(timesheet, &clock, &storage).unwrap())
+        .collect()
+    }
+
+    fn activity_list() -> Vec<ActivityListEntry> {
+        [
+            "Coding | tags: dev",
+            "Bugfixing | client: ACME | tags: dev, qa",
+            "Meeting",
+        ]
+        .iter()
+        .map(|line| ActivityListEntry::from_string(line).unwrap())
+        .collect()
+    }
+
+    fn run(input: &str) -> QueryResult {
+        Query::parse(&terms(input), date(12))
+            .unwrap()
+            .run(&day_entries(), &activity_list())
+    }
+
+    fn rows(result: &QueryResult) -> Vec<(String, i32)> {
+        result
+            .rows
+            .iter()
+            .map(|(labels, duration)| (labels.join(" / "), duration.minutes))
+            .collect()
+    }
+
+    #[test]
+    fn parse_period_accepts_days_months_years_and_names() {
+        let today = date(12);
+        assert_eq!(parse_period("2021-03-05", today), Ok((date(5), date(5))));
+        assert_eq!(parse_period("2021-03", today), Ok((date(1), date(31))));
+        assert_eq!(
+            parse_period("2020-12", today),
+            Ok((
+                NaiveDate::from_ymd(2020, 12, 1),
+                NaiveDate::from_ymd(2020, 12, 31)
+            ))
+        );
+        assert_eq!(
+            parse_period("2021", today),
+            Ok((
+                NaiveDate::from_ymd(2021, 1, 1),
+                NaiveDate::from_ymd(2021, 12, 31)
+            ))
+        );
+        assert_eq!(parse_period("today", today), Ok((today, today)));
+        assert_eq!(parse_period("yesterday", today), Ok((date(11), date(11))));
+        assert_eq!(parse_period("week", today), Ok((date(8), date(14))));
+        assert_eq!(parse_period("month", today), Ok((date(1), date(31))));
+
+        assert!(parse_period("2021-13", today).is_err());
+        assert!(parse_period("21-03", today).is_err());
+        assert!(parse_period("March", today).is_err());
+    }
+
+    #[test]
+    fn parse_date_range_combines_two_periods() {
+        let today = date(12);
+        assert_eq!(
+            parse_date_range("2021-03..2021-04", today),
+            Ok((date(1), NaiveDate::from_ymd(2021, 4, 30)))
+        );
+        assert_eq!(
+            parse_date_range("2021-03-05..today", today),
+            Ok((date(5), today))
+        );
+        assert!(parse_date_range("2021-04..2021-03", today).is_err());
+    }
+
+    #[test]
+    fn parse_rejects_invalid_terms() {
+        for input in &[
+            "Coding",
+            "name:",
+            "color:red",
+            "-work:yes",
+            "-date:2021",
+            "work:maybe",
+            "by:color",
+            "weekday:funday",
+            "name:/(/",
+            "date:2021 date:2020",
+        ] {
+            assert!(
+                Query::parse(&terms(input), date(12)).is_err(),
+                "'{}' should not parse",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn run_sums_work_without_the_time_after_leaving() {
+        let result = run("");
+        assert_eq!(result.total.minutes, 8 * 60);
+        assert_eq!(result.matching_dates, vec![date(11), date(12)]);
+        assert_eq!(rows(&result), vec![(String::new(), 8 * 60)]);
+
+        // Only the break between leaving and coming back counts, not the end of the day
+        assert_eq!(run("work:no").total.minutes, 60);
+        assert_eq!(run("work:any").total.minutes, 9 * 60);
+    }
+
+    #[test]
+    fn run_applies_filters() {
+        assert_eq!(run("name:cod*").total.minutes, 5 * 60);
+        assert_eq!(run("name:/^(Coding|Meeting)$/").total.minutes, 6 * 60);
+        assert_eq!(run("-name:coding").total.minutes, 3 * 60);
+        assert_eq!(run("weekday:fri").total.minutes, 3 * 60);
+        assert_eq!(run("-weekday:fri").total.minutes, 5 * 60);
+        assert_eq!(run("tag:qa").total.minutes, 2 * 60);
+        assert_eq!(run("tag:dev -client:acme").total.minutes, 5 * 60);
+        assert_eq!(run("client:acme").total.minutes, 2 * 60);
+        assert_eq!(run("date:2021-03-12").matching_dates, vec![date(12)]);
+        assert_eq!(run("date:2021-03-13..2021-03-31").total.minutes, 0);
+    }
+
+    #[test]
+    fn run_groups_by_duration_or_chronologically() {
+        assert_eq!(
+            rows(&run("by:activity")),
+            vec![
+                ("Coding".to_owned(), 5 * 60),
+                ("Bugfixing".to_owned(), 2 * 60),
+                ("Meeting".to_owned(), 60),
+            ]
+        );
+        assert_eq!(
+            rows(&run("by:tag")),
+            vec![
+                ("dev".to_owned(), 7 * 60),
+                ("qa".to_owned(), 2 * 60),
+                ("(no tag)".to_owned(), 60),
+            ]
+        );
+        assert_eq!(
+            rows(&run("by:day,activity name:coding")),
+            vec![
+                ("2021-03-11 Thu / Coding".to_owned(), 2 * 60),
+                ("2021-03-12 Fri / Coding".to_owned(), 3 * 60),
+            ]
+        );
+    }
+}
@@ -0,0 +1,444 @@
+use crate::activitylist::ActivityListEntry;
+use crate::budget::Period;
+use crate::dayentry::{write_durations_summary, Activity, DayEntry, ReportSettings};
+use crate::goals::GoalKind;
+use crate::statistics::Statistics;
+use crate::time::{TimeDuration, TimeStamp};
+
+use ct_lib_core::indexmap::IndexMap;
+
+use chrono::prelude::*;
+
+use std::fmt::Write;
+
+const HTML_STYLE: &str = "<style>
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.5em; }
+h2 { font-size: 1.2em; margin-top: 2em; }
+pre { background: #f4f4f4; padding: 1em; display: inline-block; }
+table { border-collapse: collapse; }
+th, td { padding: 2px 12px; text-align: left; white-space: nowrap; }
+td.number { text-align: right; }
+.timeline { position: relative; height: 28px; min-width: 600px; background: #eee; }
+.segment { position: absolute; top: 0; height: 100%; overflow: hidden; }
+.segment.break { background: repeating-linear-gradient(45deg, #ccc, #ccc 4px, #ddd 4px, #ddd 8px); }
+.axis { position: relative; height: 1.2em; min-width: 600px; font-size: 0.8em; color: #666; }
+.axis span { position: absolute; transform: translateX(-50%); }
+.bar { height: 1em; }
+.reached { color: #282; }
+.missed { color: #c22; font-weight: bold; }
+</style>";
+
+pub fn escape_html(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Creates a stable color for an activity name so that the same activity has the same color
+/// across all reports
+pub fn activity_color_hue(activity_name: &str) -> u32 {
+    // NOTE: This is the FNV-1a hash, we don't use the std hasher as it is not stable across
+    //       Rust versions
+    let hash = activity_name.bytes().fold(0x811c9dc5u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    });
+    hash % 360
+}
+
+fn activity_color_css(activity_name: &str) -> String {
+    format!("hsl({}, 60%, 60%)", activity_color_hue(activity_name))
+}
+
+fn activity_range_in_minutes(activity: &Activity, now: TimeStamp) -> (i32, i32) {
+    let start = activity.time_start.to_minutes_of_day();
+    (start, start + activity.duration(now).minutes)
+}
+
+/// Returns the range in minutes of the day that is covered by the given days, rounded to
+/// full hours
+fn timeline_range_in_minutes(day_entries: &[&DayEntry]) -> (i32, i32) {
+    let ranges: Vec<(i32, i32)> = day_entries
+        .iter()
+        .flat_map(|day_entry| {
+            let now = day_entry.time_end_of_entry();
+            day_entry
+                .activities
+                .iter()
+                .map(move |activity| activity_range_in_minutes(activity, now))
+        })
+        .collect();
+    let start = ranges
+        .iter()
+        .map(|(start, _end)| *start)
+        .min()
+        .unwrap_or(8 * 60);
+    let end = ranges
+        .iter()
+        .map(|(_start, end)| *end)
+        .max()
+        .unwrap_or(17 * 60);
+
+    let start = 60 * (start / 60);
+    let end = i32::max(start + 60, 60 * ((end + 59) / 60));
+    (start, end)
+}
+
+fn write_html_header(result: &mut String, title: &str) {
+    writeln!(result, "<!DOCTYPE html>").unwrap();
+    writeln!(result, "<html>\n<head>\n<meta charset=\"utf-8\">").unwrap();
+    writeln!(result, "<title>{}</title>", escape_html(title)).unwrap();
+    writeln!(result, "{}", HTML_STYLE).unwrap();
+    writeln!(result, "</head>\n<body>").unwrap();
+    writeln!(result, "<h1>{}</h1>", escape_html(title)).unwrap();
+}
+
+fn write_html_footer(result: &mut String) {
+    writeln!(result, "</body>\n</html>").unwrap();
+}
+
+fn write_timeline_axis(result: &mut String, range: (i32, i32)) {
+    let (range_start, range_end) = range;
+    let range_length = (range_end - range_start) as f32;
+    writeln!(result, "<div class=\"axis\">").unwrap();
+    for hour in (range_start / 60)..=(range_end / 60) {
+        writeln!(
+            result,
+            "<span style=\"left: {:.2}%\">{:02}:00</span>",
+            100.0 * (hour * 60 - range_start) as f32 / range_length,
+            hour
+        )
+        .unwrap();
+    }
+    writeln!(result, "</div>").unwrap();
+}
+
+fn write_timeline(result: &mut String, day_entry: &DayEntry, range: (i32, i32)) {
+    let (range_start, range_end) = range;
+    let range_length = (range_end - range_start) as f32;
+
+    writeln!(result, "<div class=\"timeline\">").unwrap();
+    let now = day_entry.time_end_of_entry();
+    for activity in &day_entry.activities {
+        let (start, end) = activity_range_in_minutes(activity, now);
+        let left_percent = 100.0 * (start - range_start) as f32 / range_length;
+        let width_percent = 100.0 * (end - start) as f32 / range_length;
+        let tooltip = escape_html(&day_entry.activity_to_string(activity));
+        if activity.is_work {
+            writeln!(
+                result,
+                "<div class=\"segment\" style=\"left: {:.2}%; width: {:.2}%; background: {}\" title=\"{}\"></div>",
+                left_percent,
+                width_percent,
+                activity_color_css(&activity.name),
+                tooltip
+            )
+            .unwrap();
+        } else {
+            writeln!(
+                result,
+                "<div class=\"segment break\" style=\"left: {:.2}%; width: {:.2}%\" title=\"{}\"></div>",
+                left_percent, width_percent, tooltip
+            )
+            .unwrap();
+        }
+    }
+    writeln!(result, "</div>").unwrap();
+}
+
+fn write_duration_breakdown(
+    result: &mut String,
+    activity_durations: &IndexMap<String, TimeDuration>,
+) {
+    let duration_max = activity_durations
+        .values()
+        .map(|duration| duration.minutes)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    writeln!(result, "<table>").unwrap();
+    for (activity_name, duration) in activity_durations {
+        writeln!(
+            result,
+            "<tr><td class=\"number\">{}</td><td>{}</td><td style=\"width: 400px\"><div class=\"bar\" style=\"width: {:.2}%; background: {}\"></div></td></tr>",
+            duration.to_string(),
+            escape_html(activity_name),
+            100.0 * duration.minutes as f32 / duration_max as f32,
+            activity_color_css(activity_name),
+        )
+        .unwrap();
+    }
+    writeln!(result, "</table>").unwrap();
+}
+
+pub fn generate_day_report_html(day_entry: &DayEntry) -> String {
+    let mut result = String::new();
+    let title = format!(
+        "Report for {}",
+        day_entry.date.format("%A %e. %b (%d.%m.%Y)")
+    );
+    write_html_header(&mut result, &title);
+
+    writeln!(result, "<h2>Timeline</h2>").unwrap();
+    let range = timeline_range_in_minutes(&[day_entry]);
+    write_timeline_axis(&mut result, range);
+    write_timeline(&mut result, day_entry, range);
+
+    writeln!(result, "<h2>Activity Durations</h2>").unwrap();
+    write_duration_breakdown(&mut result, &day_entry.get_activity_durations());
+
+    writeln!(result, "<h2>Totals</h2>").unwrap();
+    writeln!(
+        result,
+        "<pre>{}</pre>",
+        escape_html(write_durations_summary(day_entry).trim_end())
+    )
+    .unwrap();
+
+    writeln!(result, "<h2>Detailed Activity List</h2>").unwrap();
+    writeln!(result, "<table>").unwrap();
+    for activity in &day_entry.activities {
+        writeln!(
+            result,
+            "<tr><td>{} - {}</td><td class=\"number\">{}</td><td>{}</td></tr>",
+            activity.time_start.to_string(),
+            activity
+                .time_end
+                .map(|time_end| time_end.to_string())
+                .unwrap_or_else(|| {
+                    if day_entry.is_today() {
+                        "now".to_owned()
+                    } else {
+                        day_entry.time_end_of_entry().to_string()
+                    }
+                }),
+            activity.duration(day_entry.time_end_of_entry()).to_string(),
+            escape_html(&activity.name)
+        )
+        .unwrap();
+    }
+    writeln!(result, "</table>").unwrap();
+
+    write_html_footer(&mut result);
+    result
+}
+
+/// Creates a report for the week that contains the given date. Days that have no entry
+/// in `day_entries` are skipped.
+pub fn generate_week_report_html(
+    date: NaiveDate,
+    day_entries: &[DayEntry],
+    activity_list: &[ActivityListEntry],
+    settings: &ReportSettings,
+) -> String {
+    let mut result = String::new();
+    let week = date.iso_week();
+    let title = format!("Report for week {} of {}", week.week(), week.year());
+    write_html_header(&mut result, &title);
+
+    let week_start = date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+    let statistics = Statistics::create(
+        week_start,
+        week_start + chrono::Duration::days(6),
+        day_entries,
+        settings.target_work_duration,
+    );
+
+    let day_entries: Vec<&DayEntry> = day_entries
+        .iter()
+        .filter(|day_entry| day_entry.date.iso_week() == week)
+        .collect();
+
+    writeln!(result, "<h2>Timeline</h2>").unwrap();
+    let range = timeline_range_in_minutes(&day_entries);
+    writeln!(result, "<table>").unwrap();
+    writeln!(result, "<tr><td></td><td>").unwrap();
+    write_timeline_axis(&mut result, range);
+    writeln!(result, "</td><td></td></tr>").unwrap();
+    for day_entry in &day_entries {
+        writeln!(
+            result,
+            "<tr><td>{}</td><td>",
+            day_entry.date.format("%A %d.%m.")
+        )
+        .unwrap();
+        write_timeline(&mut result, day_entry, range);
+        writeln!(
+            result,
+            "</td><td class=\"number\">{}</td></tr>",
+            day_entry.get_work_duration_total().to_string()
+        )
+        .unwrap();
+    }
+    writeln!(result, "</table>").unwrap();
+
+    writeln!(result, "<h2>Activity Durations</h2>").unwrap();
+    let mut activity_durations: IndexMap<String, TimeDuration> = IndexMap::new();
+    for day_entry in &day_entries {
+        for (activity_name, duration) in day_entry.get_activity_durations() {
+            *activity_durations
+                .entry(activity_name)
+                .or_insert(TimeDuration::zero()) += duration;
+        }
+    }
+    activity_durations.sort_by(|_name_a, duration_a, _name_b, duration_b| {
+        // NOTE: The negatives forces descending sorting
+        (-duration_a.minutes).cmp(&-duration_b.minutes)
+    });
+    write_duration_breakdown(&mut result, &activity_durations);
+
+    if activity_list
+        .iter()
+        .any(|activity| !activity.goals.is_empty())
+    {
+        writeln!(result, "<h2>Goals</h2>").unwrap();
+        write_goals_summary(
+            &mut result,
+            &day_entries,
+            activity_list,
+            &activity_durations,
+        );
+    }
+
+    writeln!(result, "<h2>Totals</h2>").unwrap();
+    writeln!(result, "<table>").unwrap();
+    writeln!(
+        result,
+        "<tr><th>Day</th><th>Work</th><th>Activities (from list)</th><th>Activities (non-specific)</th><th>Break</th></tr>"
+    )
+    .unwrap();
+    let mut totals = [TimeDuration::zero(); 4];
+    for day_entry in &day_entries {
+        let durations = [
+            day_entry.get_work_duration_total(),
+            day_entry.get_work_duration_specific(),
+            day_entry.get_work_duration_non_specific(),
+            day_entry.get_break_duration(),
+        ];
+        write!(
+            result,
+            "<tr><td>{}</td>",
+            day_entry.date.format("%A %d.%m.")
+        )
+        .unwrap();
+        for (total, duration) in totals.iter_mut().zip(durations.iter()) {
+            *total += *duration;
+            write!(result, "<td class=\"number\">{}</td>", duration.to_string()).unwrap();
+        }
+        writeln!(result, "</tr>").unwrap();
+    }
+    write!(result, "<tr><th>Total</th>").unwrap();
+    for total in totals.iter() {
+        write!(result, "<th class=\"number\">{}</th>", total.to_string()).unwrap();
+    }
+    writeln!(result, "</tr>").unwrap();
+    writeln!(result, "</table>").unwrap();
+
+    writeln!(result, "<h2>Statistics</h2>").unwrap();
+    statistics.write_html_section(&mut result);
+
+    write_html_footer(&mut result);
+    result
+}
+
+fn write_goal_cell(result: &mut String, is_reached: bool, content: &str) {
+    write!(
+        result,
+        "<td class=\"number {}\">{}</td>",
+        if is_reached { "reached" } else { "missed" },
+        content
+    )
+    .unwrap();
+}
+
+/// Writes a table with the tracked durations of every activity with goals per day and for the
+/// whole week. Daily goals are checked for every tracked day, weekly goals for the week total.
+fn write_goals_summary(
+    result: &mut String,
+    day_entries: &[&DayEntry],
+    activity_list: &[ActivityListEntry],
+    week_activity_durations: &IndexMap<String, TimeDuration>,
+) {
+    let day_activity_durations: Vec<IndexMap<String, TimeDuration>> = day_entries
+        .iter()
+        .map(|day_entry| day_entry.get_activity_durations())
+        .collect();
+
+    writeln!(result, "<table>").unwrap();
+    write!(result, "<tr><th>Activity</th><th>Goal</th>").unwrap();
+    for day_entry in day_entries {
+        write!(result, "<th>{}</th>", day_entry.date.format("%a %d.%m.")).unwrap();
+    }
+    writeln!(result, "<th>Week</th><th>Result</th></tr>").unwrap();
+
+    for activity in activity_list {
+        for goal in &activity.goals {
+            write!(
+                result,
+                "<tr><td>{}</td><td>{}</td>",
+                escape_html(&activity.name),
+                goal.to_string()
+            )
+            .unwrap();
+
+            let mut days_reached_count = 0;
+            for durations in &day_activity_durations {
+                let duration = durations
+                    .get(&activity.name)
+                    .copied()
+                    .unwrap_or(TimeDuration::zero());
+                if goal.target.period == Period::Day {
+                    let is_reached = goal.is_reached(duration);
+                    if is_reached {
+                        days_reached_count += 1;
+                    }
+                    write_goal_cell(result, is_reached, &duration.to_string());
+                } else {
+                    write!(result, "<td class=\"number\">{}</td>", duration.to_string()).unwrap();
+                }
+            }
+
+            let week_duration = week_activity_durations
+                .get(&activity.name)
+                .copied()
+                .unwrap_or(TimeDuration::zero());
+            match goal.target.period {
+                Period::Day => {
+                    write!(
+                        result,
+                        "<td class=\"number\">{}</td>",
+                        week_duration.to_string()
+                    )
+                    .unwrap();
+                    write_goal_cell(
+                        result,
+                        days_reached_count == day_entries.len(),
+                        &format!(
+                            "reached on {} of {} days",
+                            days_reached_count,
+                            day_entries.len()
+                        ),
+                    );
+                }
+                Period::Week => {
+                    let is_reached = goal.is_reached(week_duration);
+                    write_goal_cell(result, is_reached, &week_duration.to_string());
+                    write_goal_cell(
+                        result,
+                        is_reached,
+                        match (is_reached, goal.kind) {
+                            (true, _) => "reached",
+                            (false, GoalKind::AtLeast) => "not reached",
+                            (false, GoalKind::AtMost) => "exceeded",
+                        },
+                    );
+                }
+            }
+            writeln!(result, "</tr>").unwrap();
+        }
+    }
+    writeln!(result, "</table>").unwrap();
+}
@@ -0,0 +1,281 @@
+use crate::activitylist::ActivityListEntry;
+use crate::budget::compare_plan_with_actual;
+use crate::dayentry::{write_non_specific_work_warning, DayEntry, ReportSettings};
+use crate::gitlog::GitActivityLog;
+
+use std::fmt::Write;
+
+pub fn escape_markdown_table_cell(input: &str) -> String {
+    input.replace('|', "\\|")
+}
+
+pub fn generate_day_report_markdown(
+    day_entry: &DayEntry,
+    settings: &ReportSettings,
+    activity_list: &[ActivityListEntry],
+) -> Result<String, String> {
+    let mut result = String::new();
+
+    writeln!(
+        result,
+        "# Report for {}\n",
+        day_entry.date.format("%A %e. %b (%d.%m.%Y)"),
+    )
+    .unwrap();
+
+    // Activity durations
+    writeln!(result, "## Activity Durations\n").unwrap();
+    let activity_durations = day_entry.get_activity_durations();
+    if settings.rounding.is_exact() {
+        writeln!(result, "| Duration | Activity |").unwrap();
+        writeln!(result, "|---:|---|").unwrap();
+        for (activity_name, duration) in activity_durations.iter() {
+            writeln!(
+                result,
+                "| {} | {} |",
+                duration.to_string(),
+                escape_markdown_table_cell(activity_name)
+            )
+            .unwrap();
+        }
+    } else {
+        let activity_durations_rounded =
+            settings.rounding.get_activity_durations_rounded(day_entry);
+        writeln!(result, "| Duration | Rounded | Activity |").unwrap();
+        writeln!(result, "|---:|---:|---|").unwrap();
+        for (activity_name, duration) in activity_durations.iter() {
+            writeln!(
+                result,
+                "| {} | {} | {} |",
+                duration.to_string(),
+                activity_durations_rounded[activity_name].to_string(),
+                escape_markdown_table_cell(activity_name)
+            )
+            .unwrap();
+        }
+        writeln!(
+            result,
+            "\nRounding: {}, rounded total work duration: {}",
+            settings.rounding.to_string(),
+            settings
+                .rounding
+                .get_work_duration_total_rounded(day_entry)
+                .to_string()
+        )
+        .unwrap();
+    }
+
+    // Totals summary
+    writeln!(result, "\n## Totals\n").unwrap();
+    let work_duration_total = day_entry.get_work_duration_total();
+    let work_duration_activities = day_entry.get_work_duration_specific();
+    let work_duration_non_specific = day_entry.get_work_duration_non_specific();
+    let work_percent_specific = (100.0
+        * (work_duration_activities.minutes as f32 / work_duration_total.minutes as f32))
+        .round() as usize;
+    let work_percent_non_specific = 100 - work_percent_specific;
+    writeln!(result, "| | Duration | Share |").unwrap();
+    writeln!(result, "|---|---:|---:|").unwrap();
+    writeln!(
+        result,
+        "| Total work duration | {} | 100% |",
+        work_duration_total.to_string()
+    )
+    .unwrap();
+    writeln!(
+        result,
+        "| Activities (from list) | {} | {}% |",
+        work_duration_activities.to_string(),
+        work_percent_specific
+    )
+    .unwrap();
+    writeln!(
+        result,
+        "| Activities (non-specific) | {} | {}% |",
+        work_duration_non_specific.to_string(),
+        work_percent_non_specific
+    )
+    .unwrap();
+    writeln!(
+        result,
+        "| Total break duration | {} | |",
+        day_entry.get_break_duration().to_string()
+    )
+    .unwrap();
+    if let Some(leave_duration) = day_entry.get_leave_duration() {
+        writeln!(
+            result,
+            "| Time since last leave | {} | |",
+            leave_duration.to_string()
+        )
+        .unwrap();
+    }
+
+    if let Some(warning) = write_non_specific_work_warning(day_entry, settings) {
+        writeln!(result, "\n> {}", warning).unwrap();
+    }
+
+    // Planned budgets
+    let comparisons = compare_plan_with_actual(day_entry, activity_list)?;
+    if !comparisons.is_empty() {
+        writeln!(result, "\n## Plan vs. Actual\n").unwrap();
+        writeln!(result, "| Activity | Budget | Actual | Remaining |").unwrap();
+        writeln!(result, "|---|---:|---:|---:|").unwrap();
+        for comparison in comparisons.iter() {
+            let remaining = comparison.remaining().to_string();
+            writeln!(
+                result,
+                "| {} | {} | {} | {} |",
+                escape_markdown_table_cell(&comparison.activity_name),
+                comparison.budget.to_string(),
+                comparison.tracked.to_string(),
+                if comparison.is_over_budget() {
+                    format!("**{} (over budget)**", remaining)
+                } else {
+                    remaining
+                }
+            )
+            .unwrap();
+        }
+    }
+
+    // Activity list
+    writeln!(result, "\n## Detailed Activity List\n").unwrap();
+    writeln!(result, "| Start | End | Duration | Activity |").unwrap();
+    writeln!(result, "|---|---|---:|---|").unwrap();
+    for activity in day_entry.activities.iter() {
+        writeln!(
+            result,
+            "| {} | {} | {} | {} |",
+            activity.time_start.to_string(),
+            activity
+                .time_end
+                .map(|time_end| time_end.to_string())
+                .unwrap_or_else(|| {
+                    if day_entry.is_today() {
+                        "now".to_owned()
+                    } else {
+                        day_entry.time_end_of_entry().to_string()
+                    }
+                }),
+            activity.duration(day_entry.time_end_of_entry()).to_string(),
+            escape_markdown_table_cell(&activity.name)
+        )
+        .unwrap();
+    }
+
+    // Commits
+    let git_log = GitActivityLog::collect(
+        day_entry,
+        &settings.git_repositories,
+        settings.git_author.as_deref(),
+    );
+    if !git_log.is_empty() {
+        writeln!(result, "\n## Commits\n").unwrap();
+        for (index, activity) in day_entry.activities.iter().enumerate() {
+            let commits = &git_log.commits_per_activity[index];
+            if commits.is_empty() {
+                continue;
+            }
+            writeln!(
+                result,
+                "- **{} {}**",
+                activity.time_start.to_string(),
+                activity.name
+            )
+            .unwrap();
+            for commit in commits {
+                writeln!(
+                    result,
+                    "  - {} {} `{}` {}",
+                    commit.datetime.format("%H:%M"),
+                    commit.repository_name,
+                    commit.hash,
+                    commit.summary
+                )
+                .unwrap();
+            }
+            if let Some((activity_name, commit_count)) =
+                git_log.suggest_activity_name(index, activity)
+            {
+                writeln!(
+                    result,
+                    "  - *Suggested activity: {} ({} commits)*",
+                    activity_name, commit_count
+                )
+                .unwrap();
+            }
+        }
+        for error in &git_log.errors {
+            writeln!(result, "\n> Warning: {}", error).unwrap();
+        }
+    }
+
+    Ok(result)
+}
+
+fn write_standup_activities(result: &mut String, day_entry: &DayEntry) {
+    let current_activity_name = day_entry
+        .get_current_activity()
+        .filter(|activity| activity.is_work)
+        .map(|activity| activity.name.clone());
+
+    let activity_durations = day_entry.get_activity_durations();
+    if activity_durations.is_empty() {
+        writeln!(result, "- Nothing tracked").unwrap();
+    }
+    for (activity_name, duration) in activity_durations.iter() {
+        let is_current = current_activity_name.as_ref() == Some(activity_name);
+        writeln!(
+            result,
+            "- {} ({}){}",
+            activity_name,
+            duration.to_string(),
+            if is_current { " - in progress" } else { "" }
+        )
+        .unwrap();
+    }
+}
+
+/// Creates a standup summary for the given day. `previous_day` is the last day before that
+/// on which something was tracked (i.e. last friday for a standup on monday). The notes section
+/// at the end is a placeholder for the user.
+pub fn generate_standup_markdown(day_entry: &DayEntry, previous_day: Option<&DayEntry>) -> String {
+    let mut result = String::new();
+
+    writeln!(
+        result,
+        "## Standup {}\n",
+        day_entry.date.format("%A %d.%m.%Y")
+    )
+    .unwrap();
+
+    if let Some(previous_day) = previous_day {
+        writeln!(
+            result,
+            "**Yesterday** ({}, {} total)\n",
+            previous_day.date.format("%A %d.%m."),
+            previous_day.get_work_duration_total().to_string()
+        )
+        .unwrap();
+        write_standup_activities(&mut result, previous_day);
+    } else {
+        writeln!(result, "**Yesterday**\n").unwrap();
+        writeln!(result, "- Nothing tracked").unwrap();
+    }
+
+    writeln!(
+        result,
+        "\n**Today** ({} so far)\n",
+        day_entry.get_work_duration_total().to_string()
+    )
+    .unwrap();
+    write_standup_activities(&mut result, day_entry);
+
+    // NOTE: Thyme knows nothing about blockers so this section is a placeholder that is meant
+    //       to be filled in by hand before posting the summary
+    writeln!(result, "\n**Notes / Blockers**\n").unwrap();
+    writeln!(result, "- _(fill in before posting)_").unwrap();
+
+    result
+}
@@ -0,0 +1,144 @@
+use crate::dayentry::DayEntry;
+use crate::time::TimeDuration;
+
+use ct_lib_core::indexmap::IndexMap;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RoundingMode {
+    Up,
+    Down,
+    Nearest,
+}
+
+impl RoundingMode {
+    pub fn from_string(input: &str) -> Option<RoundingMode> {
+        match input {
+            "up" => Some(RoundingMode::Up),
+            "down" => Some(RoundingMode::Down),
+            "nearest" => Some(RoundingMode::Nearest),
+            _ => None,
+        }
+    }
+
+    pub fn to_string(self) -> String {
+        match self {
+            RoundingMode::Up => "up",
+            RoundingMode::Down => "down",
+            RoundingMode::Nearest => "nearest",
+        }
+        .to_owned()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RoundingScope {
+    /// Every single activity entry is rounded on its own
+    PerEntry,
+    /// The summed up duration of an activity is rounded once per day
+    PerDayPerActivity,
+}
+
+impl RoundingScope {
+    pub fn from_string(input: &str) -> Option<RoundingScope> {
+        match input {
+            "entry" => Some(RoundingScope::PerEntry),
+            "day" => Some(RoundingScope::PerDayPerActivity),
+            _ => None,
+        }
+    }
+}
+
+/// Describes how tracked durations are rounded for reports and exports. The timesheets
+/// themselves always stay exact.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RoundingPolicy {
+    pub granularity_minutes: i32,
+    pub mode: RoundingMode,
+    pub scope: RoundingScope,
+}
+
+impl RoundingPolicy {
+    pub fn exact() -> RoundingPolicy {
+        RoundingPolicy {
+            granularity_minutes: 1,
+            mode: RoundingMode::Up,
+            scope: RoundingScope::PerEntry,
+        }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.granularity_minutes <= 1
+    }
+
+    pub fn to_string(self) -> String {
+        if self.is_exact() {
+            return "exact".to_owned();
+        }
+        format!(
+            "{} to {} minutes per {}",
+            self.mode.to_string(),
+            self.granularity_minutes,
+            match self.scope {
+                RoundingScope::PerEntry => "entry",
+                RoundingScope::PerDayPerActivity => "activity per day",
+            }
+        )
+    }
+
+    pub fn round(&self, duration: TimeDuration) -> TimeDuration {
+        if self.is_exact() {
+            return duration;
+        }
+
+        let granularity = self.granularity_minutes;
+        let remainder = duration.minutes.rem_euclid(granularity);
+        if remainder == 0 {
+            return duration;
+        }
+        let rounded_down = duration.minutes - remainder;
+        let minutes = match self.mode {
+            RoundingMode::Up => rounded_down + granularity,
+            RoundingMode::Down => rounded_down,
+            RoundingMode::Nearest => {
+                if 2 * remainder >= granularity {
+                    rounded_down + granularity
+                } else {
+                    rounded_down
+                }
+            }
+        };
+        TimeDuration { minutes }
+    }
+
+    /// Same as `DayEntry::get_activity_durations` but with every duration rounded according to
+    /// this policy. The order of the activities is kept the same as in the unrounded version.
+    pub fn get_activity_durations_rounded(
+        &self,
+        day_entry: &DayEntry,
+    ) -> IndexMap<String, TimeDuration> {
+        day_entry
+            .get_activity_durations()
+            .into_iter()
+            .map(|(activity_name, duration)| {
+                let duration_rounded = match self.scope {
+                    RoundingScope::PerEntry => day_entry
+                        .activities
+                        .iter()
+                        .filter(|activity| activity.is_work)
+                        .filter(|activity| activity.name == activity_name)
+                        .fold(TimeDuration::zero(), |acc, activity| {
+                            acc + self.round(activity.duration(day_entry.time_end_of_entry()))
+                        }),
+                    RoundingScope::PerDayPerActivity => self.round(duration),
+                };
+                (activity_name, duration_rounded)
+            })
+            .collect()
+    }
+
+    pub fn get_work_duration_total_rounded(&self, day_entry: &DayEntry) -> TimeDuration {
+        self.get_activity_durations_rounded(day_entry)
+            .values()
+            .fold(TimeDuration::zero(), |acc, duration| acc + *duration)
+    }
+}
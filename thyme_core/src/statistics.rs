@@ -0,0 +1,624 @@
+use crate::dayentry::{Activity, DayEntry};
+use crate::report_html::escape_html;
+use crate::report_markdown::escape_markdown_table_cell;
+use crate::time::{TimeDuration, TimeStamp};
+
+use ct_lib_core::indexmap::IndexMap;
+
+use chrono::prelude::*;
+
+use std::fmt::Write;
+
+const SPARKLINE_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Number of most recent weeks that are shown as columns in the activity share tables. The trend
+/// sparklines always cover all weeks.
+const SHARE_WEEKS_SHOWN: usize = 8;
+
+/// Returns one character per value where the height is relative to the largest value. Missing
+/// values are shown as blanks.
+pub fn sparkline(values: &[Option<i32>]) -> String {
+    let max = values.iter().flatten().copied().max().unwrap_or(0).max(1);
+    values
+        .iter()
+        .map(|value| match value {
+            Some(value) => {
+                let level =
+                    (*value).max(0) as f32 / max as f32 * (SPARKLINE_LEVELS.len() - 1) as f32;
+                SPARKLINE_LEVELS[level.round() as usize]
+            }
+            None => ' ',
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct LongestActivity {
+    pub date: NaiveDate,
+    pub activity: Activity,
+    pub duration: TimeDuration,
+}
+
+/// Share of an activity in the work duration of each week in percent. `None` for weeks in which
+/// nothing was tracked.
+#[derive(Debug, Clone)]
+pub struct ActivityShares {
+    pub activity_name: String,
+    pub total: TimeDuration,
+    pub shares_percent: Vec<Option<i32>>,
+}
+
+/// Statistics about the working habits over a range of days
+#[derive(Debug, Clone)]
+pub struct Statistics {
+    pub date_from: NaiveDate,
+    pub date_to: NaiveDate,
+    pub target_work_duration: TimeDuration,
+    /// Number of days with at least one activity
+    pub day_count: usize,
+    pub first_checkin_average: Option<TimeStamp>,
+    pub first_checkin_median: Option<TimeStamp>,
+    /// Only days that are over, i.e. that end with a leave
+    pub checkout_average: Option<TimeStamp>,
+    pub work_average: Option<TimeDuration>,
+    pub break_average: Option<TimeDuration>,
+    pub days_over_target: usize,
+    pub longest_activity: Option<LongestActivity>,
+    /// ISO year and week of every week in the range
+    pub weeks: Vec<(i32, u32)>,
+    /// Sorted by the total duration in descending order
+    pub activity_shares: Vec<ActivityShares>,
+    /// Work duration of every calendar day in the range or `None` if nothing was tracked
+    pub daily_totals: Vec<(NaiveDate, Option<TimeDuration>)>,
+}
+
+fn average_minutes(values: &[i32]) -> Option<i32> {
+    if values.is_empty() {
+        None
+    } else {
+        Some((values.iter().sum::<i32>() as f32 / values.len() as f32).round() as i32)
+    }
+}
+
+fn median_minutes(values: &[i32]) -> Option<i32> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut values = values.to_vec();
+    values.sort_unstable();
+    // NOTE: For an even count this is the mean of the two middle values
+    Some((values[(values.len() - 1) / 2] + values[values.len() / 2]) / 2)
+}
+
+fn optional_to_string<T>(value: Option<T>, to_string: impl Fn(T) -> String) -> String {
+    value.map(to_string).unwrap_or_else(|| "-".to_owned())
+}
+
+impl Statistics {
+    /// Creates the statistics of all given days within `date_from` and `date_to`
+    pub fn create(
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        day_entries: &[DayEntry],
+        target_work_duration: TimeDuration,
+    ) -> Statistics {
+        let day_entries: Vec<&DayEntry> = day_entries
+            .iter()
+            .filter(|day_entry| date_from <= day_entry.date && day_entry.date <= date_to)
+            .filter(|day_entry| !day_entry.activities.is_empty())
+            .collect();
+
+        let checkins: Vec<i32> = day_entries
+            .iter()
+            .filter_map(|day_entry| day_entry.first_checkin_time())
+            .map(|time| time.to_minutes_of_day())
+            .collect();
+        let checkouts: Vec<i32> = day_entries
+            .iter()
+            .filter_map(|day_entry| day_entry.last_checkout_time())
+            .map(|time| time.to_minutes_of_day())
+            .collect();
+        let work_durations: Vec<i32> = day_entries
+            .iter()
+            .map(|day_entry| day_entry.get_work_duration_total().minutes)
+            .collect();
+        let break_durations: Vec<i32> = day_entries
+            .iter()
+            .map(|day_entry| day_entry.get_break_duration().minutes)
+            .collect();
+
+        let mut longest_activity: Option<LongestActivity> = None;
+        for day_entry in &day_entries {
+            let now = day_entry.time_end_of_entry();
+            for activity in day_entry
+                .activities
+                .iter()
+                .filter(|activity| activity.is_work)
+            {
+                let duration = activity.duration(now);
+                if longest_activity
+                    .as_ref()
+                    .map(|longest| duration.minutes > longest.duration.minutes)
+                    .unwrap_or(true)
+                {
+                    longest_activity = Some(LongestActivity {
+                        date: day_entry.date,
+                        activity: activity.clone(),
+                        duration,
+                    });
+                }
+            }
+        }
+
+        let mut weeks = Vec::new();
+        let mut daily_totals = Vec::new();
+        let mut date = date_from;
+        while date <= date_to {
+            let week = (date.iso_week().year(), date.iso_week().week());
+            if weeks.last() != Some(&week) {
+                weeks.push(week);
+            }
+            let total = day_entries
+                .iter()
+                .find(|day_entry| day_entry.date == date)
+                .map(|day_entry| day_entry.get_work_duration_total());
+            daily_totals.push((date, total));
+            date = date.succ();
+        }
+
+        let mut week_totals = vec![0; weeks.len()];
+        let mut activity_week_durations: IndexMap<String, Vec<i32>> = IndexMap::new();
+        for day_entry in &day_entries {
+            let week = (
+                day_entry.date.iso_week().year(),
+                day_entry.date.iso_week().week(),
+            );
+            let week_index = weeks.iter().position(|entry| *entry == week).unwrap();
+            for (activity_name, duration) in day_entry.get_activity_durations() {
+                week_totals[week_index] += duration.minutes;
+                activity_week_durations
+                    .entry(activity_name)
+                    .or_insert_with(|| vec![0; weeks.len()])[week_index] += duration.minutes;
+            }
+        }
+        let mut activity_shares: Vec<ActivityShares> = activity_week_durations
+            .into_iter()
+            .map(|(activity_name, durations)| ActivityShares {
+                activity_name,
+                total: TimeDuration {
+                    minutes: durations.iter().sum(),
+                },
+                shares_percent: durations
+                    .iter()
+                    .zip(&week_totals)
+                    .map(|(duration, week_total)| {
+                        if *week_total == 0 {
+                            None
+                        } else {
+                            Some((100.0 * *duration as f32 / *week_total as f32).round() as i32)
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+        // NOTE: The negatives forces descending sorting
+        activity_shares.sort_by_key(|shares| -shares.total.minutes);
+
+        Statistics {
+            date_from,
+            date_to,
+            target_work_duration,
+            day_count: day_entries.len(),
+            first_checkin_average: average_minutes(&checkins).map(TimeStamp::from_minutes_of_day),
+            first_checkin_median: median_minutes(&checkins).map(TimeStamp::from_minutes_of_day),
+            checkout_average: average_minutes(&checkouts).map(TimeStamp::from_minutes_of_day),
+            work_average: average_minutes(&work_durations).map(|minutes| TimeDuration { minutes }),
+            break_average: average_minutes(&break_durations)
+                .map(|minutes| TimeDuration { minutes }),
+            days_over_target: work_durations
+                .iter()
+                .filter(|minutes| **minutes > target_work_duration.minutes)
+                .count(),
+            longest_activity,
+            weeks,
+            activity_shares,
+            daily_totals,
+        }
+    }
+
+    pub fn daily_totals_sparkline(&self) -> String {
+        let values: Vec<Option<i32>> = self
+            .daily_totals
+            .iter()
+            .map(|(_, total)| total.map(|total| total.minutes))
+            .collect();
+        sparkline(&values)
+    }
+
+    fn daily_totals_maximum(&self) -> Option<(NaiveDate, TimeDuration)> {
+        self.daily_totals
+            .iter()
+            .filter_map(|(date, total)| total.map(|total| (*date, total)))
+            .max_by_key(|(_, total)| total.minutes)
+    }
+
+    fn trend_sparkline(shares: &ActivityShares) -> String {
+        sparkline(&shares.shares_percent)
+    }
+
+    fn share_to_string(share: Option<i32>) -> String {
+        optional_to_string(share, |share| format!("{}%", share))
+    }
+
+    fn shown_weeks_start(&self) -> usize {
+        self.weeks.len().saturating_sub(SHARE_WEEKS_SHOWN)
+    }
+
+    fn title(&self) -> String {
+        format!(
+            "Statistics for {} - {}",
+            self.date_from.format("%Y-%m-%d"),
+            self.date_to.format("%Y-%m-%d")
+        )
+    }
+
+    /// Label and value of every key figure
+    fn key_figures(&self) -> Vec<(String, String)> {
+        vec![
+            ("Tracked days".to_owned(), self.day_count.to_string()),
+            (
+                "First check-in".to_owned(),
+                format!(
+                    "average {}, median {}",
+                    optional_to_string(self.first_checkin_average, |time| time.to_string()),
+                    optional_to_string(self.first_checkin_median, |time| time.to_string()),
+                ),
+            ),
+            (
+                "End of work".to_owned(),
+                format!(
+                    "average {}",
+                    optional_to_string(self.checkout_average, |time| time.to_string())
+                ),
+            ),
+            (
+                "Work per day".to_owned(),
+                format!(
+                    "average {}",
+                    optional_to_string(self.work_average, |duration| duration.to_string())
+                ),
+            ),
+            (
+                "Break per day".to_owned(),
+                format!(
+                    "average {}",
+                    optional_to_string(self.break_average, |duration| duration.to_string())
+                ),
+            ),
+            (
+                format!(
+                    "Days over target ({})",
+                    self.target_work_duration.to_string()
+                ),
+                format!("{} of {}", self.days_over_target, self.day_count),
+            ),
+            (
+                "Longest activity".to_owned(),
+                optional_to_string(self.longest_activity.as_ref(), |longest| {
+                    format!(
+                        "{} on {}, {} - {} ({})",
+                        longest.activity.name,
+                        longest.date.format("%Y-%m-%d"),
+                        longest.activity.time_start.to_string(),
+                        optional_to_string(longest.activity.time_end, |time| time.to_string()),
+                        longest.duration.to_string()
+                    )
+                }),
+            ),
+        ]
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut result = String::new();
+        writeln!(result, "{}\n", self.title()).unwrap();
+
+        let key_figures = self.key_figures();
+        let label_width = key_figures
+            .iter()
+            .map(|(label, _)| label.chars().count() + 1)
+            .max()
+            .unwrap_or(0);
+        for (label, value) in &key_figures {
+            writeln!(
+                result,
+                "{:<width$}  {}",
+                format!("{}:", label),
+                value,
+                width = label_width
+            )
+            .unwrap();
+        }
+
+        if let Some((date, maximum)) = self.daily_totals_maximum() {
+            writeln!(
+                result,
+                "\nDaily work totals (maximum {} on {}, blank = nothing tracked):",
+                maximum.to_string(),
+                date.format("%Y-%m-%d")
+            )
+            .unwrap();
+            writeln!(
+                result,
+                "{} {} {}",
+                self.date_from.format("%Y-%m-%d"),
+                self.daily_totals_sparkline(),
+                self.date_to.format("%Y-%m-%d")
+            )
+            .unwrap();
+        }
+
+        if !self.activity_shares.is_empty() {
+            let weeks = &self.weeks[self.shown_weeks_start()..];
+            let name_width = self
+                .activity_shares
+                .iter()
+                .map(|shares| shares.activity_name.chars().count())
+                .max()
+                .unwrap_or(0)
+                .max("Activity".len());
+
+            writeln!(result, "\nShare of the weekly work duration:").unwrap();
+            let mut header = format!("{:<width$}", "Activity", width = name_width);
+            for (_year, week) in weeks {
+                write!(header, "  {:>4}", format!("W{:02}", week)).unwrap();
+            }
+            write!(header, "  Trend").unwrap();
+            let separator = "-".repeat(header.chars().count() + self.weeks.len());
+            writeln!(result, "{}\n{}", header, separator).unwrap();
+            for shares in &self.activity_shares {
+                write!(
+                    result,
+                    "{:<width$}",
+                    shares.activity_name,
+                    width = name_width
+                )
+                .unwrap();
+                for share in &shares.shares_percent[self.shown_weeks_start()..] {
+                    write!(result, "  {:>4}", Statistics::share_to_string(*share)).unwrap();
+                }
+                writeln!(result, "  {}", Statistics::trend_sparkline(shares)).unwrap();
+            }
+        }
+        result
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut result = String::new();
+        writeln!(result, "# {}\n", self.title()).unwrap();
+
+        writeln!(result, "| | |\n|---|---|").unwrap();
+        for (label, value) in self.key_figures() {
+            writeln!(
+                result,
+                "| {} | {} |",
+                escape_markdown_table_cell(&label),
+                escape_markdown_table_cell(&value)
+            )
+            .unwrap();
+        }
+
+        if let Some((date, maximum)) = self.daily_totals_maximum() {
+            writeln!(result, "\n## Daily work totals\n").unwrap();
+            writeln!(
+                result,
+                "Maximum {} on {}, blank = nothing tracked\n",
+                maximum.to_string(),
+                date.format("%Y-%m-%d")
+            )
+            .unwrap();
+            writeln!(
+                result,
+                "```\n{} {} {}\n```",
+                self.date_from.format("%Y-%m-%d"),
+                self.daily_totals_sparkline(),
+                self.date_to.format("%Y-%m-%d")
+            )
+            .unwrap();
+        }
+
+        if !self.activity_shares.is_empty() {
+            let weeks = &self.weeks[self.shown_weeks_start()..];
+            writeln!(result, "\n## Share of the weekly work duration\n").unwrap();
+            write!(result, "| Activity |").unwrap();
+            for (year, week) in weeks {
+                write!(result, " {}-W{:02} |", year, week).unwrap();
+            }
+            writeln!(result, " Trend |").unwrap();
+            write!(result, "|---|").unwrap();
+            for _ in weeks {
+                write!(result, "---:|").unwrap();
+            }
+            writeln!(result, "---|").unwrap();
+            for shares in &self.activity_shares {
+                write!(
+                    result,
+                    "| {} |",
+                    escape_markdown_table_cell(&shares.activity_name)
+                )
+                .unwrap();
+                for share in &shares.shares_percent[self.shown_weeks_start()..] {
+                    write!(result, " {} |", Statistics::share_to_string(*share)).unwrap();
+                }
+                writeln!(result, " `{}` |", Statistics::trend_sparkline(shares)).unwrap();
+            }
+        }
+        result
+    }
+
+    /// Writes the statistics as a section of an HTML report. The share trends are left out if the
+    /// range does not span multiple weeks.
+    pub fn write_html_section(&self, result: &mut String) {
+        writeln!(result, "<table>").unwrap();
+        for (label, value) in self.key_figures() {
+            writeln!(
+                result,
+                "<tr><td>{}</td><td>{}</td></tr>",
+                escape_html(&label),
+                escape_html(&value)
+            )
+            .unwrap();
+        }
+        writeln!(result, "</table>").unwrap();
+
+        if let Some((date, maximum)) = self.daily_totals_maximum() {
+            writeln!(
+                result,
+                "<p>Daily work totals (maximum {} on {}):</p>",
+                maximum.to_string(),
+                date.format("%Y-%m-%d")
+            )
+            .unwrap();
+            writeln!(
+                result,
+                "<pre>{} {} {}</pre>",
+                self.date_from.format("%Y-%m-%d"),
+                self.daily_totals_sparkline(),
+                self.date_to.format("%Y-%m-%d")
+            )
+            .unwrap();
+        }
+
+        if self.weeks.len() > 1 && !self.activity_shares.is_empty() {
+            let weeks = &self.weeks[self.shown_weeks_start()..];
+            writeln!(result, "<p>Share of the weekly work duration:</p>").unwrap();
+            write!(result, "<table>\n<tr><th>Activity</th>").unwrap();
+            for (year, week) in weeks {
+                write!(result, "<th>{}-W{:02}</th>", year, week).unwrap();
+            }
+            writeln!(result, "<th>Trend</th></tr>").unwrap();
+            for shares in &self.activity_shares {
+                write!(
+                    result,
+                    "<tr><td>{}</td>",
+                    escape_html(&shares.activity_name)
+                )
+                .unwrap();
+                for share in &shares.shares_percent[self.shown_weeks_start()..] {
+                    write!(
+                        result,
+                        "<td class=\"number\">{}</td>",
+                        Statistics::share_to_string(*share)
+                    )
+                    .unwrap();
+                }
+                writeln!(
+                    result,
+                    "<td><code>{}</code></td></tr>",
+                    Statistics::trend_sparkline(shares)
+                )
+                .unwrap();
+            }
+            writeln!(result, "</table>").unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FakeClock, SharedClock};
+    use crate::storage::{MemoryStorage, SharedStorage};
+
+    use std::sync::Arc;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd(2021, 3, day)
+    }
+
+    #[test]
+    fn median_minutes_of_odd_and_even_counts() {
+        assert_eq!(median_minutes(&[]), None);
+        assert_eq!(median_minutes(&[480]), Some(480));
+        assert_eq!(median_minutes(&[540, 480, 600]), Some(540));
+        // The mean of the two middle values
+        assert_eq!(median_minutes(&[600, 480, 500, 900]), Some(550));
+    }
+
+    #[test]
+    fn average_minutes_rounds_to_the_nearest_minute() {
+        assert_eq!(average_minutes(&[]), None);
+        assert_eq!(average_minutes(&[480, 481]), Some(481));
+        assert_eq!(average_minutes(&[480, 480, 481]), Some(480));
+    }
+
+    #[test]
+    fn sparkline_is_relative_to_the_largest_value() {
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(sparkline(&[Some(0), Some(7), Some(14)]), "▁▅█");
+        assert_eq!(sparkline(&[Some(10), None, Some(5)]), "█ ▅");
+        // Negative values are shown like zeros and all zeros do not divide by zero
+        assert_eq!(sparkline(&[Some(-5), Some(0)]), "▁▁");
+    }
+
+    #[test]
+    fn create_skips_days_without_activities_and_outside_the_range() {
+        let clock: SharedClock = Arc::new(FakeClock::new(date(20).and_hms(12, 0, 0)));
+        let storage: SharedStorage = MemoryStorage::shared();
+        let day_entries: Vec<DayEntry> = [
+            "Timesheet for 2021-03-10\n\
+             07:00 - Begin [Coding]\n\
+             12:00 - Leave\n",
+            "Timesheet for 2021-03-11\n\
+             08:00 - Begin [Coding]\n\
+             12:00 - Leave\n\
+             12:30 - Begin [Meeting]\n\
+             17:30 - Leave\n",
+            "Timesheet for 2021-03-12\n",
+            "Timesheet for 2021-03-15\n\
+             09:00 - Begin [Coding]\n\
+             15:00 - Leave\n",
+        ]
+        .iter()
+        .map(|timesheet| DayEntry::from_timesheet_string(timesheet, &clock, &storage).unwrap())
+        .collect();
+
+        let statistics = Statistics::create(
+            date(11),
+            date(15),
+            &day_entries,
+            TimeDuration { minutes: 8 * 60 },
+        );
+        assert_eq!(statistics.day_count, 2);
+        assert_eq!(
+            statistics.first_checkin_average,
+            Some(TimeStamp::new(8, 30))
+        );
+        assert_eq!(statistics.checkout_average, Some(TimeStamp::new(16, 15)));
+        assert_eq!(
+            statistics.work_average.map(|d| d.minutes),
+            Some(7 * 60 + 30)
+        );
+        assert_eq!(statistics.break_average.map(|d| d.minutes), Some(15));
+        assert_eq!(statistics.days_over_target, 1);
+        assert_eq!(statistics.weeks, vec![(2021, 10), (2021, 11)]);
+        assert_eq!(statistics.daily_totals.len(), 5);
+        assert_eq!(statistics.daily_totals_sparkline(), "█   ▆");
+
+        let longest = statistics.longest_activity.unwrap();
+        assert_eq!(longest.date, date(15));
+        assert_eq!(longest.duration.minutes, 6 * 60);
+
+        let shares: Vec<(&str, Vec<Option<i32>>)> = statistics
+            .activity_shares
+            .iter()
+            .map(|shares| (shares.activity_name.as_str(), shares.shares_percent.clone()))
+            .collect();
+        assert_eq!(
+            shares,
+            vec![
+                ("Coding", vec![Some(44), Some(100)]),
+                ("Meeting", vec![Some(56), Some(0)]),
+            ]
+        );
+    }
+}
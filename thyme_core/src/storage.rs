@@ -0,0 +1,435 @@
+use ct_lib_core::{path_exists, path_last_modified_time, path_without_filename};
+
+use chrono::prelude::*;
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// Name of the working copy of today's timesheet in a `TextFileStorage`
+pub const TODAY_TIMESHEET_FILEPATH: &str = "today__timesheet.txt";
+
+const DATE_FORMAT_DATABASE: &str = "%Y_%m_%d__%b_%A";
+
+/// Identifies a timesheet within a storage
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Timesheet {
+    /// The working copy of today's timesheet which can be edited while Thyme is running
+    Today,
+    /// The timesheet of the given day in the database
+    Day(NaiveDate),
+}
+
+/// Identifies a generated report within a storage. Reports are only ever written.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Report {
+    /// The live updated report of today with the given file extension
+    Today(&'static str),
+    /// The report of the given day with the given file extension
+    Day(NaiveDate, &'static str),
+    /// The weekly HTML report of the given ISO year and week
+    Week(i32, u32),
+}
+
+/// Where timesheets are read from and where timesheets and reports are written to. The text
+/// file backend is used by Thyme itself while the in-memory backend allows loading, saving and
+/// reporting without touching the file system.
+pub trait Storage {
+    /// Returns `None` if the timesheet does not exist
+    fn read_timesheet(&self, timesheet: Timesheet) -> Result<Option<String>, String>;
+    fn write_timesheet(&self, timesheet: Timesheet, content: &str) -> Result<(), String>;
+    /// Returns a value that grows with every change of the timesheet or `None` if it does not
+    /// exist. Used to detect external changes.
+    fn timesheet_modified_time(&self, timesheet: Timesheet) -> Option<f64>;
+    /// Human readable location of the timesheet for error messages
+    fn timesheet_location(&self, timesheet: Timesheet) -> String;
+    /// Returns the dates of all days in the database in ascending order
+    fn list_timesheet_dates(&self) -> Result<Vec<NaiveDate>, String>;
+
+    fn write_report(&self, report: Report, content: &str) -> Result<(), String>;
+    /// Removes the report if it exists
+    fn remove_report(&self, report: Report) -> Result<(), String>;
+}
+
+/// A storage that can be shared between day entries and threads
+pub type SharedStorage = Arc<dyn Storage + Send + Sync>;
+
+/// Copies all timesheets of the database and today's timesheet from one storage to another.
+/// Timesheets that cannot be copied are skipped. Returns the number of copied days and an error
+/// for every skipped timesheet.
+pub fn copy_timesheets(
+    source: &dyn Storage,
+    destination: &dyn Storage,
+) -> Result<(usize, Vec<String>), String> {
+    let mut timesheets: Vec<Timesheet> = source
+        .list_timesheet_dates()?
+        .into_iter()
+        .map(Timesheet::Day)
+        .collect();
+    timesheets.push(Timesheet::Today);
+
+    let mut day_count = 0;
+    let mut errors = Vec::new();
+    for timesheet in timesheets {
+        let result = source
+            .read_timesheet(timesheet)
+            .and_then(|content| match content {
+                Some(content) => destination
+                    .write_timesheet(timesheet, &content)
+                    .map(|_| true),
+                None => Ok(false),
+            });
+        match result {
+            Ok(true) if timesheet != Timesheet::Today => day_count += 1,
+            Ok(_) => {}
+            Err(error) => errors.push(format!(
+                "Could not copy '{}' - {}",
+                source.timesheet_location(timesheet),
+                error
+            )),
+        }
+    }
+    Ok((day_count, errors))
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Text files
+
+/// Stores everything as human readable text files in a `database` directory. Today's timesheet
+/// and report are additionally kept at the top of the directory for easy editing.
+pub struct TextFileStorage {
+    directory: PathBuf,
+}
+
+impl TextFileStorage {
+    /// An empty path refers to the current directory
+    pub fn new(directory: &Path) -> TextFileStorage {
+        TextFileStorage {
+            directory: directory.to_path_buf(),
+        }
+    }
+
+    pub fn shared(directory: &Path) -> SharedStorage {
+        Arc::new(TextFileStorage::new(directory))
+    }
+
+    fn timesheet_filepath(&self, timesheet: Timesheet) -> String {
+        let filename = match timesheet {
+            Timesheet::Today => TODAY_TIMESHEET_FILEPATH.to_owned(),
+            Timesheet::Day(date) => format!(
+                "database/{}__timesheet.txt",
+                date.format(DATE_FORMAT_DATABASE)
+            ),
+        };
+        self.directory.join(filename).to_string_lossy().to_string()
+    }
+
+    fn report_filepath(&self, report: Report) -> String {
+        let filename = match report {
+            Report::Today(extension) => format!("today__report.{}", extension),
+            Report::Day(date, extension) => format!(
+                "database/{}__report.{}",
+                date.format(DATE_FORMAT_DATABASE),
+                extension
+            ),
+            Report::Week(year, week) => format!("database/{}_week_{:02}__report.html", year, week),
+        };
+        self.directory.join(filename).to_string_lossy().to_string()
+    }
+
+    fn write_file(filepath: &str, content: &str) -> Result<(), String> {
+        let directory_path = path_without_filename(filepath);
+        if !directory_path.is_empty() && !path_exists(&directory_path) {
+            std::fs::create_dir_all(&directory_path).map_err(|error| {
+                format!("Could not create path '{}' - {}", &directory_path, error)
+            })?;
+        }
+        // NOTE: We write to a temporary file first and rename it afterwards so that the file is
+        //       never left half written if writing fails
+        let temporary_filepath = format!("{}.tmp", filepath);
+        std::fs::write(&temporary_filepath, content)
+            .and_then(|_| std::fs::rename(&temporary_filepath, filepath))
+            .map_err(|error| {
+                std::fs::remove_file(&temporary_filepath).ok();
+                format!("Could not write to '{}' - {}", filepath, error)
+            })
+    }
+}
+
+impl Storage for TextFileStorage {
+    fn read_timesheet(&self, timesheet: Timesheet) -> Result<Option<String>, String> {
+        let filepath = self.timesheet_filepath(timesheet);
+        if !path_exists(&filepath) {
+            return Ok(None);
+        }
+        std::fs::read_to_string(&filepath)
+            .map(Some)
+            .map_err(|error| format!("Could not read '{}' - {}", &filepath, error))
+    }
+
+    fn write_timesheet(&self, timesheet: Timesheet, content: &str) -> Result<(), String> {
+        TextFileStorage::write_file(&self.timesheet_filepath(timesheet), content)
+    }
+
+    fn timesheet_modified_time(&self, timesheet: Timesheet) -> Option<f64> {
+        let filepath = self.timesheet_filepath(timesheet);
+        if path_exists(&filepath) {
+            Some(path_last_modified_time(&filepath))
+        } else {
+            None
+        }
+    }
+
+    fn timesheet_location(&self, timesheet: Timesheet) -> String {
+        self.timesheet_filepath(timesheet)
+    }
+
+    fn list_timesheet_dates(&self) -> Result<Vec<NaiveDate>, String> {
+        let database_path = self.directory.join("database");
+        if !database_path.exists() {
+            return Ok(Vec::new());
+        }
+        let entries = std::fs::read_dir(&database_path)
+            .map_err(|error| format!("Could not read '{}' - {}", database_path.display(), error))?;
+
+        let mut result = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|error| {
+                format!("Could not read '{}' - {}", database_path.display(), error)
+            })?;
+            let filename = entry.file_name().to_string_lossy().to_string();
+            // NOTE: Only the numeric part of the date is needed, i.e. '2021_03_12' of
+            //       '2021_03_12__Mar_Friday__timesheet.txt'
+            if filename.ends_with("__timesheet.txt") && filename.len() >= 10 {
+                if let Ok(date) = NaiveDate::parse_from_str(&filename[..10], "%Y_%m_%d") {
+                    result.push(date);
+                }
+            }
+        }
+        result.sort();
+        Ok(result)
+    }
+
+    fn write_report(&self, report: Report, content: &str) -> Result<(), String> {
+        TextFileStorage::write_file(&self.report_filepath(report), content)
+    }
+
+    fn remove_report(&self, report: Report) -> Result<(), String> {
+        let filepath = self.report_filepath(report);
+        match std::fs::remove_file(&filepath) {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Could not remove '{}' - {}", filepath, error))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Memory
+
+#[derive(Default)]
+struct MemoryStorageContent {
+    timesheets: BTreeMap<Timesheet, (String, f64)>,
+    reports: BTreeMap<Report, String>,
+    revision: f64,
+}
+
+/// Keeps everything in memory. Useful for tests and for tools that want to generate reports
+/// from timesheets without writing anything.
+#[derive(Default)]
+pub struct MemoryStorage {
+    content: Mutex<MemoryStorageContent>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> MemoryStorage {
+        MemoryStorage::default()
+    }
+
+    pub fn shared() -> Arc<MemoryStorage> {
+        Arc::new(MemoryStorage::new())
+    }
+
+    /// Returns the last written version of the given report
+    pub fn report(&self, report: Report) -> Option<String> {
+        self.content.lock().unwrap().reports.get(&report).cloned()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn read_timesheet(&self, timesheet: Timesheet) -> Result<Option<String>, String> {
+        let content = self.content.lock().unwrap();
+        Ok(content
+            .timesheets
+            .get(&timesheet)
+            .map(|(timesheet, _)| timesheet.clone()))
+    }
+
+    fn write_timesheet(&self, timesheet: Timesheet, text: &str) -> Result<(), String> {
+        let mut content = self.content.lock().unwrap();
+        content.revision += 1.0;
+        let revision = content.revision;
+        content
+            .timesheets
+            .insert(timesheet, (text.to_owned(), revision));
+        Ok(())
+    }
+
+    fn timesheet_modified_time(&self, timesheet: Timesheet) -> Option<f64> {
+        let content = self.content.lock().unwrap();
+        content
+            .timesheets
+            .get(&timesheet)
+            .map(|(_, revision)| *revision)
+    }
+
+    fn timesheet_location(&self, timesheet: Timesheet) -> String {
+        match timesheet {
+            Timesheet::Today => "memory:today".to_owned(),
+            Timesheet::Day(date) => format!("memory:{}", date.format("%Y-%m-%d")),
+        }
+    }
+
+    fn list_timesheet_dates(&self) -> Result<Vec<NaiveDate>, String> {
+        let content = self.content.lock().unwrap();
+        Ok(content
+            .timesheets
+            .keys()
+            .filter_map(|timesheet| match timesheet {
+                Timesheet::Today => None,
+                Timesheet::Day(date) => Some(*date),
+            })
+            .collect())
+    }
+
+    fn write_report(&self, report: Report, text: &str) -> Result<(), String> {
+        let mut content = self.content.lock().unwrap();
+        content.reports.insert(report, text.to_owned());
+        Ok(())
+    }
+
+    fn remove_report(&self, report: Report) -> Result<(), String> {
+        let mut content = self.content.lock().unwrap();
+        content.reports.remove(&report);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd(2021, 3, 12)
+    }
+
+    #[test]
+    fn memory_storage_reads_back_written_timesheets() {
+        let storage = MemoryStorage::new();
+        assert_eq!(storage.read_timesheet(Timesheet::Today).unwrap(), None);
+        assert_eq!(storage.timesheet_modified_time(Timesheet::Today), None);
+
+        storage.write_timesheet(Timesheet::Today, "today").unwrap();
+        storage
+            .write_timesheet(Timesheet::Day(date()), "friday")
+            .unwrap();
+        assert_eq!(
+            storage.read_timesheet(Timesheet::Today).unwrap().as_deref(),
+            Some("today")
+        );
+        assert_eq!(
+            storage
+                .read_timesheet(Timesheet::Day(date()))
+                .unwrap()
+                .as_deref(),
+            Some("friday")
+        );
+        assert_eq!(
+            storage
+                .read_timesheet(Timesheet::Day(date().succ()))
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn memory_storage_modified_time_grows_with_every_write() {
+        let storage = MemoryStorage::new();
+        storage.write_timesheet(Timesheet::Today, "first").unwrap();
+        let first = storage.timesheet_modified_time(Timesheet::Today).unwrap();
+
+        storage
+            .write_timesheet(Timesheet::Day(date()), "other")
+            .unwrap();
+        storage.write_timesheet(Timesheet::Today, "second").unwrap();
+        let second = storage.timesheet_modified_time(Timesheet::Today).unwrap();
+        assert!(first < second);
+    }
+
+    #[test]
+    fn memory_storage_keeps_the_last_version_of_reports() {
+        let storage = MemoryStorage::new();
+        assert_eq!(storage.report(Report::Day(date(), "txt")), None);
+
+        storage
+            .write_report(Report::Day(date(), "txt"), "first")
+            .unwrap();
+        storage
+            .write_report(Report::Day(date(), "txt"), "second")
+            .unwrap();
+        storage
+            .write_report(Report::Day(date(), "html"), "html")
+            .unwrap();
+        assert_eq!(
+            storage.report(Report::Day(date(), "txt")).as_deref(),
+            Some("second")
+        );
+        assert_eq!(
+            storage.report(Report::Day(date(), "html")).as_deref(),
+            Some("html")
+        );
+        assert_eq!(storage.report(Report::Today("txt")), None);
+
+        storage.remove_report(Report::Day(date(), "txt")).unwrap();
+        storage.remove_report(Report::Today("txt")).unwrap();
+        assert_eq!(storage.report(Report::Day(date(), "txt")), None);
+        assert_eq!(
+            storage.report(Report::Day(date(), "html")).as_deref(),
+            Some("html")
+        );
+    }
+
+    #[test]
+    fn text_file_storage_replaces_timesheets_without_leaving_temporary_files() {
+        let directory = std::env::temp_dir().join(format!(
+            "thyme_text_file_storage_test_{}",
+            std::process::id()
+        ));
+        let storage = TextFileStorage::new(&directory);
+
+        storage
+            .write_timesheet(Timesheet::Day(date()), "first")
+            .unwrap();
+        storage
+            .write_timesheet(Timesheet::Day(date()), "second")
+            .unwrap();
+        assert_eq!(
+            storage
+                .read_timesheet(Timesheet::Day(date()))
+                .unwrap()
+                .as_deref(),
+            Some("second")
+        );
+        assert_eq!(storage.list_timesheet_dates().unwrap(), vec![date()]);
+        let filenames: Vec<String> = std::fs::read_dir(directory.join("database"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(filenames, vec!["2021_03_12__Mar_Friday__timesheet.txt"]);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
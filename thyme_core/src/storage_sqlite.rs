@@ -0,0 +1,321 @@
+use crate::dayentry::{Activity, DayEntry};
+use crate::storage::{Report, SharedStorage, Storage, TextFileStorage, Timesheet};
+use crate::time::TimeStamp;
+
+use chrono::prelude::*;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const DATE_FORMAT_SQLITE: &str = "%Y-%m-%d";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS days (
+    date TEXT PRIMARY KEY NOT NULL,
+    modified_time REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS activities (
+    date TEXT NOT NULL REFERENCES days(date),
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    is_work INTEGER NOT NULL,
+    start_minute INTEGER NOT NULL,
+    end_minute INTEGER,
+    PRIMARY KEY (date, position)
+);
+CREATE TABLE IF NOT EXISTS today (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    date TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS reports (
+    name TEXT PRIMARY KEY NOT NULL,
+    content TEXT NOT NULL
+);
+";
+
+/// Stores days and their activities in a SQLite database. Activities are kept as rows with their
+/// start and end given in minutes of the day (the end is NULL for ongoing activities), so that
+/// the database can also be queried directly.
+///
+/// Optionally all timesheets and reports are mirrored to human readable text files. Timesheets
+/// that were edited in the mirror are preferred over the database until they are written again.
+pub struct SqliteStorage {
+    filepath: String,
+    connection: Mutex<Connection>,
+    mirror: Option<TextFileStorage>,
+}
+
+impl SqliteStorage {
+    pub fn open(filepath: &str, mirror: Option<TextFileStorage>) -> Result<SqliteStorage, String> {
+        let connection = Connection::open(filepath)
+            .map_err(|error| format!("Could not open '{}' - {}", filepath, error))?;
+        connection
+            .execute_batch(SCHEMA)
+            .map_err(|error| format!("Could not create tables in '{}' - {}", filepath, error))?;
+        Ok(SqliteStorage {
+            filepath: filepath.to_owned(),
+            connection: Mutex::new(connection),
+            mirror,
+        })
+    }
+
+    pub fn shared(
+        filepath: &str,
+        mirror: Option<TextFileStorage>,
+    ) -> Result<SharedStorage, String> {
+        Ok(Arc::new(SqliteStorage::open(filepath, mirror)?))
+    }
+
+    fn error(&self, error: rusqlite::Error) -> String {
+        format!("Could not access '{}' - {}", self.filepath, error)
+    }
+
+    fn report_name(report: Report) -> String {
+        match report {
+            Report::Today(extension) => format!("today.{}", extension),
+            Report::Day(date, extension) => {
+                format!("{}.{}", date.format(DATE_FORMAT_SQLITE), extension)
+            }
+            Report::Week(year, week) => format!("{}-W{:02}.html", year, week),
+        }
+    }
+
+    fn today_date(&self, connection: &Connection) -> Result<Option<String>, String> {
+        connection
+            .query_row("SELECT date FROM today WHERE id = 0", [], |row| row.get(0))
+            .optional()
+            .map_err(|error| self.error(error))
+    }
+
+    fn date_of(
+        &self,
+        connection: &Connection,
+        timesheet: Timesheet,
+    ) -> Result<Option<String>, String> {
+        match timesheet {
+            Timesheet::Today => self.today_date(connection),
+            Timesheet::Day(date) => Ok(Some(date.format(DATE_FORMAT_SQLITE).to_string())),
+        }
+    }
+
+    fn read_day(&self, timesheet: Timesheet) -> Result<Option<String>, String> {
+        let connection = self.connection.lock().unwrap();
+        let date = match self.date_of(&connection, timesheet)? {
+            Some(date) => date,
+            None => return Ok(None),
+        };
+        let exists: Option<String> = connection
+            .query_row("SELECT date FROM days WHERE date = ?1", [&date], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(|error| self.error(error))?;
+        if exists.is_none() {
+            return Ok(None);
+        }
+
+        let mut statement = connection
+            .prepare(
+                "SELECT name, is_work, start_minute, end_minute FROM activities
+                 WHERE date = ?1 ORDER BY position",
+            )
+            .map_err(|error| self.error(error))?;
+        let activities = statement
+            .query_map([&date], |row| {
+                Ok(Activity {
+                    name: row.get(0)?,
+                    is_work: row.get(1)?,
+                    time_start: TimeStamp::from_minutes_of_day(row.get(2)?),
+                    time_end: row
+                        .get::<_, Option<i32>>(3)?
+                        .map(TimeStamp::from_minutes_of_day),
+                })
+            })
+            .map_err(|error| self.error(error))?
+            .collect::<Result<Vec<Activity>, _>>()
+            .map_err(|error| self.error(error))?;
+
+        let date = NaiveDate::parse_from_str(&date, DATE_FORMAT_SQLITE).map_err(|error| {
+            format!("Invalid date '{}' in '{}' - {}", date, self.filepath, error)
+        })?;
+        Ok(Some(DayEntry::timesheet_to_string(date, &activities)))
+    }
+
+    fn write_day(
+        &self,
+        timesheet: Timesheet,
+        content: &str,
+        modified_time: f64,
+    ) -> Result<(), String> {
+        let (date, activities) = DayEntry::parse_timesheet(content)?;
+        let date = date.format(DATE_FORMAT_SQLITE).to_string();
+
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection
+            .transaction()
+            .map_err(|error| self.error(error))?;
+        transaction
+            .execute("DELETE FROM activities WHERE date = ?1", [&date])
+            .map_err(|error| self.error(error))?;
+        transaction
+            .execute(
+                "INSERT OR REPLACE INTO days (date, modified_time) VALUES (?1, ?2)",
+                params![date, modified_time],
+            )
+            .map_err(|error| self.error(error))?;
+        for (position, activity) in activities.iter().enumerate() {
+            transaction
+                .execute(
+                    "INSERT INTO activities (date, position, name, is_work, start_minute, end_minute)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        date,
+                        position as i64,
+                        activity.name,
+                        activity.is_work,
+                        activity.time_start.to_minutes_of_day(),
+                        activity.time_end.map(|end| end.to_minutes_of_day()),
+                    ],
+                )
+                .map_err(|error| self.error(error))?;
+        }
+        if timesheet == Timesheet::Today {
+            transaction
+                .execute(
+                    "INSERT OR REPLACE INTO today (id, date) VALUES (0, ?1)",
+                    [&date],
+                )
+                .map_err(|error| self.error(error))?;
+        }
+        transaction.commit().map_err(|error| self.error(error))
+    }
+
+    fn day_modified_time(&self, timesheet: Timesheet) -> Option<f64> {
+        let connection = self.connection.lock().unwrap();
+        let date = self.date_of(&connection, timesheet).ok()??;
+        connection
+            .query_row(
+                "SELECT modified_time FROM days WHERE date = ?1",
+                [&date],
+                |row| row.get(0),
+            )
+            .optional()
+            .ok()?
+    }
+
+    fn mirror_is_newer(&self, timesheet: Timesheet) -> bool {
+        let mirror_modified_time = self
+            .mirror
+            .as_ref()
+            .and_then(|mirror| mirror.timesheet_modified_time(timesheet));
+        match (mirror_modified_time, self.day_modified_time(timesheet)) {
+            (Some(mirror), Some(database)) => mirror > database,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn read_timesheet(&self, timesheet: Timesheet) -> Result<Option<String>, String> {
+        match &self.mirror {
+            Some(mirror) if self.mirror_is_newer(timesheet) => mirror.read_timesheet(timesheet),
+            _ => self.read_day(timesheet),
+        }
+    }
+
+    fn write_timesheet(&self, timesheet: Timesheet, content: &str) -> Result<(), String> {
+        // NOTE: The database gets a modification time that is not older than the one of the
+        //       mirror so that only later external changes of the mirror are preferred
+        let mut modified_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs_f64())
+            .unwrap_or(0.0);
+        if let Some(mirror) = &self.mirror {
+            mirror.write_timesheet(timesheet, content)?;
+            if let Some(mirror_modified_time) = mirror.timesheet_modified_time(timesheet) {
+                modified_time = modified_time.max(mirror_modified_time);
+            }
+        }
+        self.write_day(timesheet, content, modified_time)
+    }
+
+    fn timesheet_modified_time(&self, timesheet: Timesheet) -> Option<f64> {
+        let mirror_modified_time = self
+            .mirror
+            .as_ref()
+            .and_then(|mirror| mirror.timesheet_modified_time(timesheet));
+        match (mirror_modified_time, self.day_modified_time(timesheet)) {
+            (Some(mirror), Some(database)) => Some(mirror.max(database)),
+            (mirror, database) => mirror.or(database),
+        }
+    }
+
+    fn timesheet_location(&self, timesheet: Timesheet) -> String {
+        match (&self.mirror, timesheet) {
+            (Some(mirror), _) if self.mirror_is_newer(timesheet) => {
+                mirror.timesheet_location(timesheet)
+            }
+            (_, Timesheet::Today) => format!("{} (today)", self.filepath),
+            (_, Timesheet::Day(date)) => {
+                format!("{} ({})", self.filepath, date.format(DATE_FORMAT_SQLITE))
+            }
+        }
+    }
+
+    fn list_timesheet_dates(&self) -> Result<Vec<NaiveDate>, String> {
+        let mut result = match &self.mirror {
+            Some(mirror) => mirror.list_timesheet_dates()?,
+            None => Vec::new(),
+        };
+
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection
+            .prepare("SELECT date FROM days")
+            .map_err(|error| self.error(error))?;
+        let dates = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|error| self.error(error))?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|error| self.error(error))?;
+        for date in dates {
+            let date = NaiveDate::parse_from_str(&date, DATE_FORMAT_SQLITE).map_err(|error| {
+                format!("Invalid date '{}' in '{}' - {}", date, self.filepath, error)
+            })?;
+            result.push(date);
+        }
+        result.sort();
+        result.dedup();
+        Ok(result)
+    }
+
+    fn write_report(&self, report: Report, content: &str) -> Result<(), String> {
+        if let Some(mirror) = &self.mirror {
+            mirror.write_report(report, content)?;
+        }
+        let name = SqliteStorage::report_name(report);
+        let connection = self.connection.lock().unwrap();
+        connection
+            .execute(
+                "INSERT OR REPLACE INTO reports (name, content) VALUES (?1, ?2)",
+                params![name, content],
+            )
+            .map_err(|error| self.error(error))?;
+        Ok(())
+    }
+
+    fn remove_report(&self, report: Report) -> Result<(), String> {
+        if let Some(mirror) = &self.mirror {
+            mirror.remove_report(report)?;
+        }
+        let name = SqliteStorage::report_name(report);
+        let connection = self.connection.lock().unwrap();
+        connection
+            .execute("DELETE FROM reports WHERE name = ?1", params![name])
+            .map_err(|error| self.error(error))?;
+        Ok(())
+    }
+}
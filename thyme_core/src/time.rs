@@ -16,18 +16,6 @@ impl DateTimeHelper for NaiveDateTime {
     }
 }
 
-pub fn get_current_time() -> NaiveTime {
-    Local::now().naive_local().time()
-}
-
-pub fn get_current_date() -> NaiveDate {
-    Local::now().naive_local().date()
-}
-
-pub fn get_current_datetime() -> NaiveDateTime {
-    Local::now().naive_local()
-}
-
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct TimeStamp {
     pub hours: u32,
@@ -41,34 +29,34 @@ impl TimeStamp {
         TimeStamp { hours, minutes }
     }
 
-    pub fn from_string(input: &str) -> TimeStamp {
-        let mut parts = input.split(":");
-        let hours = parts
-            .next()
-            .unwrap_or_else(|| panic!("The string '{}' is not a valid timestamp", input))
-            .parse()
-            .unwrap_or_else(|error| {
-                panic!("The string '{}' is not a valid timestamp: {}", input, error)
-            });
-        let minutes = parts
-            .next()
-            .unwrap_or_else(|| panic!("The string '{}' is not a valid timestamp", input))
-            .parse()
-            .unwrap_or_else(|error| {
-                panic!("The string '{}' is not a valid timestamp: {}", input, error)
-            });
-
-        assert!(
-            parts.next().is_none(),
-            "The string '{}' is not a valid timestamp",
-            input
-        );
-        TimeStamp::new(hours, minutes)
-    }
-
-    pub fn to_string(&self) -> String {
+    /// Parses timestamps of the form `HH:MM`
+    pub fn from_string(input: &str) -> Result<TimeStamp, String> {
+        let (hours, minutes) = input
+            .split_once(':')
+            .ok_or_else(|| format!("The string '{}' is not a valid timestamp", input))?;
+        let hours: u32 = hours.parse().map_err(|error| {
+            format!("The string '{}' is not a valid timestamp: {}", input, error)
+        })?;
+        let minutes: u32 = minutes.parse().map_err(|error| {
+            format!("The string '{}' is not a valid timestamp: {}", input, error)
+        })?;
+        if hours >= 24 || minutes >= 60 {
+            return Err(format!("The string '{}' is not a valid timestamp", input));
+        }
+        Ok(TimeStamp::new(hours, minutes))
+    }
+
+    pub fn to_string(self) -> String {
         format!("{:02}:{:02}", self.hours, self.minutes)
     }
+
+    pub fn to_minutes_of_day(self) -> i32 {
+        (self.hours * 60 + self.minutes) as i32
+    }
+
+    pub fn from_minutes_of_day(minutes: i32) -> TimeStamp {
+        TimeStamp::new(minutes as u32 / 60, minutes as u32 % 60)
+    }
 }
 
 use std::ops::Add;
@@ -106,21 +94,34 @@ impl TimeDuration {
         TimeDuration { minutes: 0 }
     }
 
-    pub fn to_string(&self) -> String {
+    /// Parses durations of the form `HH:MM` with an optional trailing `h` (i.e. `07:30h`)
+    pub fn from_string(input: &str) -> Option<TimeDuration> {
+        let input = input.trim();
+        let input = input.strip_suffix('h').unwrap_or(input);
+        let (hours, minutes) = input.split_once(':')?;
+        let hours: u32 = hours.trim().parse().ok()?;
+        let minutes: u32 = minutes.trim().parse().ok()?;
+        if minutes >= 60 {
+            return None;
+        }
+        Some(TimeDuration {
+            minutes: (hours * 60 + minutes) as i32,
+        })
+    }
+
+    /// Formats the duration as `HH:MM` with a trailing `h`. Durations can be longer than a day.
+    pub fn to_string(self) -> String {
         format!(
-            "{}{}h",
+            "{}{:02}:{:02}h",
             if self.minutes < 0 { "-" } else { "" },
-            TimeStamp::new(
-                self.minutes.abs() as u32 / 60,
-                self.minutes.abs() as u32 % 60
-            )
-            .to_string()
+            self.minutes.abs() / 60,
+            self.minutes.abs() % 60
         )
     }
 
-    pub fn to_string_blinking_shortened(&self, blink: bool) -> String {
-        let hours = self.minutes.abs() as u32 / 60;
-        let minutes = self.minutes.abs() as u32 % 60;
+    pub fn to_string_blinking_shortened(self, blink: bool) -> String {
+        let hours = self.minutes.unsigned_abs() / 60;
+        let minutes = self.minutes.unsigned_abs() % 60;
 
         let separator = if blink { ":" } else { " " };
         if hours == 0 {